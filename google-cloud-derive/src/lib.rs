@@ -10,7 +10,8 @@ mod casing;
 
 use crate::casing::{transform_field_casing, transform_variant_casing};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, FromMeta)]
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromMeta)]
 pub(crate) enum RenameAll {
     #[darling(rename = "lowercase")]
     LowerCase,
@@ -19,6 +20,7 @@ pub(crate) enum RenameAll {
     #[darling(rename = "PascalCase")]
     PascalCase,
     #[darling(rename = "camelCase")]
+    #[default]
     CamelCase,
     #[darling(rename = "snake_case")]
     SnakeCase,
@@ -30,12 +32,6 @@ pub(crate) enum RenameAll {
     ScreamingKebabCase,
 }
 
-impl Default for RenameAll {
-    fn default() -> RenameAll {
-        RenameAll::CamelCase
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, FromDeriveInput)]
 #[darling(attributes(datastore), supports(struct_named, enum_unit))]
 struct Container {
@@ -2,24 +2,36 @@ use std::fs;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let protos = [
-        (["protos/google/pubsub/v1/pubsub.proto"], "src/pubsub/api"),
+        (
+            ["protos/google/pubsub/v1/pubsub.proto"],
+            "src/pubsub/api",
+            false,
+        ),
         (
             ["protos/google/datastore/v1/datastore.proto"],
             "src/datastore/api",
+            false,
         ),
         (
             ["protos/google/cloud/vision/v1/image_annotator.proto"],
             "src/vision/api",
+            false,
+        ),
+        // The server is used to test the gRPC transport against a local fake.
+        (
+            ["protos/google/bytestream/bytestream.proto"],
+            "src/storage/api",
+            true,
         ),
     ];
 
-    for (proto_files, out_dir) in protos.iter() {
-        fs::create_dir_all(&out_dir)?;
+    for (proto_files, out_dir, build_server) in protos.iter() {
+        fs::create_dir_all(out_dir)?;
 
         tonic_build::configure()
             .build_client(true)
-            .build_server(false)
-            .out_dir(&out_dir)
+            .build_server(*build_server)
+            .out_dir(out_dir)
             .compile(proto_files, &["protos"])?;
 
         for file in proto_files {
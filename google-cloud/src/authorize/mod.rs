@@ -59,7 +59,8 @@ pub(crate) struct TokenManagerSecure {
 }
 
 pub(crate) enum TokenManager {
-    Secure(TokenManagerSecure),
+    Secure(Box<TokenManagerSecure>),
+    #[cfg(any(feature = "pubsub", feature = "storage"))]
     Insecure,
 }
 
@@ -70,17 +71,27 @@ struct AuthResponse {
 
 impl TokenManager {
     pub(crate) fn new(creds: ApplicationCredentials, scopes: &[&str]) -> TokenManager {
-        TokenManager::Secure(TokenManagerSecure {
+        TokenManager::Secure(Box::new(TokenManagerSecure {
             creds,
             client: Client::builder().build::<_, hyper::Body>(HttpsConnector::with_native_roots()),
             scopes: scopes.join(" "),
             current_token: None,
-        })
+        }))
+    }
+
+    #[cfg(feature = "storage")]
+    pub(crate) fn credentials(&self) -> Option<&ApplicationCredentials> {
+        match self {
+            TokenManager::Secure(t) => Some(&t.creds),
+            #[cfg(any(feature = "pubsub", feature = "storage"))]
+            TokenManager::Insecure => None,
+        }
     }
 
     pub(crate) async fn token(&mut self) -> Result<String, AuthError> {
         match self {
             TokenManager::Secure(t) => t.token().await,
+            #[cfg(any(feature = "pubsub", feature = "storage"))]
             TokenManager::Insecure => Ok(String::new()),
         }
     }
@@ -104,7 +115,7 @@ impl TokenManagerSecure {
                 let token = jwt::encode(
                     &jwt::Header::new(jwt::Algorithm::RS256),
                     &claims,
-                    &jwt::EncodingKey::from_rsa_pem(&self.creds.private_key.as_bytes())?,
+                    &jwt::EncodingKey::from_rsa_pem(self.creds.private_key.as_bytes())?,
                 )?;
                 let form = format!(
                     "grant_type=urn:ietf:params:oauth:grant-type:jwt-bearer&assertion={}",
@@ -0,0 +1,292 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::datastore::api;
+use crate::datastore::api::aggregation_query::aggregation::{Avg, Count, Operator, Sum};
+use crate::datastore::client::{convert_consistency, convert_query};
+use crate::datastore::{Client, Error, Query, ReadOptions, Value};
+
+/// Represents an aggregation function, computed over the results of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregation {
+    /// Count of the matching entities, optionally stopping at an upper bound.
+    Count {
+        /// The name of the aggregation result.
+        alias: String,
+        /// The maximum number of entities to count.
+        up_to: Option<i64>,
+    },
+    /// Sum of the numeric values of a property.
+    Sum {
+        /// The name of the aggregation result.
+        alias: String,
+        /// The property to sum.
+        property: String,
+    },
+    /// Average of the numeric values of a property.
+    Avg {
+        /// The name of the aggregation result.
+        alias: String,
+        /// The property to average.
+        property: String,
+    },
+}
+
+impl Aggregation {
+    /// Get the name of the aggregation result.
+    pub fn alias(&self) -> &str {
+        match self {
+            Aggregation::Count { alias, .. }
+            | Aggregation::Sum { alias, .. }
+            | Aggregation::Avg { alias, .. } => alias.as_str(),
+        }
+    }
+}
+
+/// Represents a Datastore aggregation query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AggregationQuery {
+    pub(crate) query: Query,
+    pub(crate) aggregations: Vec<Aggregation>,
+}
+
+impl AggregationQuery {
+    /// The maximum number of aggregations per query.
+    pub const MAX_AGGREGATIONS: usize = 5;
+
+    /// Construct a new aggregation query, over the results of a query.
+    ///
+    /// ```
+    /// # use google_cloud::datastore::{AggregationQuery, Filter, Query};
+    /// let query = Query::new("users").filter(Filter::prop("age").gte(18));
+    /// let aggregation = AggregationQuery::new(query)
+    ///     .count("total")
+    ///     .avg("average_age", "age");
+    /// ```
+    pub fn new(query: Query) -> AggregationQuery {
+        AggregationQuery {
+            query,
+            aggregations: Vec::new(),
+        }
+    }
+
+    /// Count the matching entities.
+    pub fn count(self, alias: impl Into<String>) -> AggregationQuery {
+        self.aggregation(Aggregation::Count {
+            alias: alias.into(),
+            up_to: None,
+        })
+    }
+
+    /// Count the matching entities, stopping at `up_to` of them (to limit latency and costs).
+    pub fn count_up_to(self, alias: impl Into<String>, up_to: i64) -> AggregationQuery {
+        self.aggregation(Aggregation::Count {
+            alias: alias.into(),
+            up_to: Some(up_to),
+        })
+    }
+
+    /// Sum the values of a property of the matching entities.
+    pub fn sum(self, alias: impl Into<String>, property: impl Into<String>) -> AggregationQuery {
+        self.aggregation(Aggregation::Sum {
+            alias: alias.into(),
+            property: property.into(),
+        })
+    }
+
+    /// Average the values of a property of the matching entities.
+    pub fn avg(self, alias: impl Into<String>, property: impl Into<String>) -> AggregationQuery {
+        self.aggregation(Aggregation::Avg {
+            alias: alias.into(),
+            property: property.into(),
+        })
+    }
+
+    /// Add an aggregation to compute.
+    pub fn aggregation(mut self, aggregation: Aggregation) -> AggregationQuery {
+        self.aggregations.push(aggregation);
+        self
+    }
+
+    /// Checks the aggregations and their query before sending them.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.query.validate()?;
+        if self.aggregations.is_empty() || self.aggregations.len() > Self::MAX_AGGREGATIONS {
+            return Err(Error::InvalidArgument(format!(
+                "aggregation queries must have between 1 and {} aggregations",
+                Self::MAX_AGGREGATIONS,
+            )));
+        }
+        let mut aliases = HashSet::new();
+        for aggregation in self.aggregations.iter() {
+            if !aliases.insert(aggregation.alias()) {
+                return Err(Error::InvalidArgument(format!(
+                    "the aggregation alias `{}` is used more than once",
+                    aggregation.alias(),
+                )));
+            }
+            if let Aggregation::Count {
+                up_to: Some(up_to), ..
+            } = aggregation
+            {
+                if *up_to < 0 {
+                    return Err(Error::InvalidArgument(String::from(
+                        "count upper bounds must be non-negative",
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Query> for AggregationQuery {
+    fn from(query: Query) -> AggregationQuery {
+        AggregationQuery::new(query)
+    }
+}
+
+/// The results of an aggregation query, by alias.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AggregationResult {
+    pub(crate) values: HashMap<String, Value>,
+}
+
+impl AggregationResult {
+    /// Get the result of an aggregation (`None` if it is missing or null, like the average of no values).
+    pub fn get(&self, alias: &str) -> Option<&Value> {
+        self.values.get(alias)
+    }
+
+    /// Get the result of an aggregation as an integer (like counts and sums of integers).
+    pub fn get_i64(&self, alias: &str) -> Option<i64> {
+        match self.get(alias)? {
+            Value::IntegerValue(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the result of an aggregation as a floating-point number (like averages and any sum).
+    pub fn get_f64(&self, alias: &str) -> Option<f64> {
+        match self.get(alias)? {
+            Value::IntegerValue(value) => Some(*value as f64),
+            Value::DoubleValue(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Move the results out.
+    pub fn into_values(self) -> HashMap<String, Value> {
+        self.values
+    }
+}
+
+impl From<api::AggregationResult> for AggregationResult {
+    fn from(result: api::AggregationResult) -> AggregationResult {
+        use api::value::ValueType;
+        let values = result
+            .aggregate_properties
+            .into_iter()
+            .filter_map(|(alias, value)| match value.value_type {
+                None | Some(ValueType::NullValue(_)) => None,
+                Some(value) => Some((alias, Value::from(value))),
+            })
+            .collect();
+        AggregationResult { values }
+    }
+}
+
+impl Client {
+    /// Runs an aggregation query (like counting entities without fetching them).
+    pub async fn aggregate(
+        &mut self,
+        query: impl Into<AggregationQuery>,
+    ) -> Result<AggregationResult, Error> {
+        self.run_aggregation(query.into(), None).await
+    }
+
+    /// Runs an aggregation query with the given read options (instead of the query's own consistency).
+    pub async fn aggregate_with_options(
+        &mut self,
+        query: impl Into<AggregationQuery>,
+        options: ReadOptions,
+    ) -> Result<AggregationResult, Error> {
+        self.run_aggregation(query.into(), Some(options.into_api()))
+            .await
+    }
+
+    /// Runs an aggregation query with the given read options.
+    pub(crate) async fn run_aggregation(
+        &mut self,
+        query: AggregationQuery,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<AggregationResult, Error> {
+        query.validate()?;
+        let read_options =
+            read_options.unwrap_or_else(|| convert_consistency(query.query.eventual));
+        let aggregations = query
+            .aggregations
+            .into_iter()
+            .map(|aggregation| {
+                let property = |name| Some(api::PropertyReference { name });
+                let (alias, operator) = match aggregation {
+                    Aggregation::Count { alias, up_to } => {
+                        (alias, Operator::Count(Count { up_to }))
+                    }
+                    Aggregation::Sum {
+                        alias,
+                        property: name,
+                    } => (
+                        alias,
+                        Operator::Sum(Sum {
+                            property: property(name),
+                        }),
+                    ),
+                    Aggregation::Avg {
+                        alias,
+                        property: name,
+                    } => (
+                        alias,
+                        Operator::Avg(Avg {
+                            property: property(name),
+                        }),
+                    ),
+                };
+                api::aggregation_query::Aggregation {
+                    alias,
+                    operator: Some(operator),
+                }
+            })
+            .collect();
+        let aggregation_query = api::AggregationQuery {
+            aggregations,
+            query_type: Some(api::aggregation_query::QueryType::NestedQuery(
+                convert_query(self.project_name.as_str(), &query.query),
+            )),
+        };
+
+        let request = api::RunAggregationQueryRequest {
+            partition_id: Some(api::PartitionId {
+                project_id: self.project_name.clone(),
+                namespace_id: query.query.namespace.unwrap_or_default(),
+            }),
+            query_type: Some(
+                api::run_aggregation_query_request::QueryType::AggregationQuery(aggregation_query),
+            ),
+            read_options: Some(read_options),
+            project_id: self.project_name.clone(),
+        };
+        let request = self.construct_request(request).await?;
+        let response = self.service.run_aggregation_query(request).await?;
+        let batch = response.into_inner().batch.unwrap();
+
+        let result = batch
+            .aggregation_results
+            .into_iter()
+            .next()
+            .map(AggregationResult::from)
+            .unwrap_or_default();
+
+        Ok(result)
+    }
+}
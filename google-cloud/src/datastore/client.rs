@@ -1,9 +1,11 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::File;
 use std::sync::Arc;
 
+use chrono::NaiveDateTime;
+use futures::stream::{self, Stream, TryStreamExt};
 use tokio::sync::Mutex;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig};
 use tonic::{IntoRequest, Request};
@@ -11,9 +13,11 @@ use tonic::{IntoRequest, Request};
 use crate::authorize::{ApplicationCredentials, TokenManager, TLS_CERTS};
 use crate::datastore::api;
 use crate::datastore::api::datastore_client::DatastoreClient;
+use crate::datastore::api::run_query_request::QueryType;
 use crate::datastore::api::value::ValueType;
 use crate::datastore::{
-    Entity, Error, Filter, FromValue, IntoEntity, Key, KeyID, Order, Query, Value,
+    Entity, Error, Filter, FromValue, GqlQuery, IntoEntity, Key, KeyID, LookupResults, MoreResults,
+    Mutation, MutationResult, Order, Query, QueryResults, ReadOptions, Value,
 };
 
 /// The Datastore client, tied to a specific project.
@@ -32,6 +36,11 @@ impl Client {
         "https://www.googleapis.com/auth/datastore",
     ];
 
+    /// The maximum number of lookup requests made by `get_all`, while the store keeps deferring keys.
+    ///
+    /// Keys still deferred after that are returned in `LookupResults::deferred`.
+    pub const MAX_LOOKUP_ROUNDS: usize = 10;
+
     pub(crate) async fn construct_request<T: IntoRequest<T>>(
         &mut self,
         request: T,
@@ -84,12 +93,63 @@ impl Client {
         K: Borrow<Key>,
         T: FromValue,
     {
-        let results = self.get_all(Some(key.borrow())).await?;
-        Ok(results.into_iter().next().map(T::from_value).transpose()?)
+        let results = self.get_all::<Value, _, _>(Some(key.borrow())).await?;
+        let value = results.found.into_iter().next();
+        Ok(value.map(|(_, value)| T::from_value(value)).transpose()?)
+    }
+
+    /// Gets an entity from a key, with the given read options.
+    pub async fn get_with_options<T, K>(
+        &mut self,
+        key: K,
+        options: ReadOptions,
+    ) -> Result<Option<T>, Error>
+    where
+        K: Borrow<Key>,
+        T: FromValue,
+    {
+        let results = self
+            .get_all_with_options::<Value, _, _>(Some(key.borrow()), options)
+            .await?;
+        let value = results.found.into_iter().next();
+        Ok(value.map(|(_, value)| T::from_value(value)).transpose()?)
     }
 
     /// Gets multiple entities from multiple keys.
-    pub async fn get_all<T, K, I>(&mut self, keys: I) -> Result<Vec<T>, Error>
+    ///
+    /// Found entities are returned along with their keys, separately from the keys not found,
+    /// both in the order of the given keys.
+    pub async fn get_all<T, K, I>(&mut self, keys: I) -> Result<LookupResults<T>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: Borrow<Key>,
+        T: FromValue,
+    {
+        self.lookup(keys, None).await
+    }
+
+    /// Gets multiple entities from multiple keys, with the given read options.
+    pub async fn get_all_with_options<T, K, I>(
+        &mut self,
+        keys: I,
+        options: ReadOptions,
+    ) -> Result<LookupResults<T>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: Borrow<Key>,
+        T: FromValue,
+    {
+        self.lookup(keys, Some(options.into_api())).await
+    }
+
+    /// Looks up keys with raw API read options (like the ones of a transaction).
+    ///
+    /// Deferred keys are requested again, for up to `Client::MAX_LOOKUP_ROUNDS` requests.
+    pub(crate) async fn lookup<T, K, I>(
+        &mut self,
+        keys: I,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<LookupResults<T>, Error>
     where
         I: IntoIterator<Item = K>,
         K: Borrow<Key>,
@@ -101,12 +161,15 @@ impl Client {
             .map(|key| convert_key(self.project_name.as_str(), key.borrow()))
             .collect();
         let mut found = HashMap::new();
+        let mut missing = HashSet::new();
 
-        while !keys.is_empty() {
+        let mut rounds = 0;
+        while !keys.is_empty() && rounds < Client::MAX_LOOKUP_ROUNDS {
+            rounds += 1;
             let request = api::LookupRequest {
                 keys,
                 project_id: self.project_name.clone(),
-                read_options: None,
+                read_options: read_options.clone(),
             };
             let request = self.construct_request(request).await?;
             let response = self.service.lookup(request).await?;
@@ -120,17 +183,17 @@ impl Client {
                     .map(Entity::from)
                     .map(|entity| (entity.key, entity.properties)),
             );
-            // let missing = response.missing;
+            missing.extend(
+                response
+                    .missing
+                    .into_iter()
+                    .map(|val| Key::from(val.entity.unwrap().key.unwrap())),
+            );
             keys = response.deferred;
         }
 
-        let values: Vec<T> = og_keys
-            .into_iter()
-            .flat_map(|key| found.remove(key.borrow()))
-            .map(FromValue::from_value)
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(values)
+        let deferred = keys.into_iter().map(Key::from).collect();
+        lookup_results(&self.project_name, &og_keys, found, missing, deferred)
     }
 
     /// Inserts a new entity and returns its key.
@@ -148,35 +211,11 @@ impl Client {
         I: IntoIterator<Item = T>,
         T: IntoEntity,
     {
-        let entities: Vec<Entity> = entities
-            .into_iter()
-            .map(IntoEntity::into_entity)
-            .collect::<Result<_, _>>()?;
         let mutations = entities
             .into_iter()
-            .map(|entity| {
-                let is_incomplete = entity.key.is_incomplete();
-                let entity = convert_entity(self.project_name.as_str(), entity);
-                api::Mutation {
-                    operation: if is_incomplete {
-                        Some(api::mutation::Operation::Insert(entity))
-                    } else {
-                        Some(api::mutation::Operation::Upsert(entity))
-                    },
-                    conflict_detection_strategy: None,
-                }
-            })
-            .collect();
-
-        let request = api::CommitRequest {
-            mutations,
-            mode: api::commit_request::Mode::NonTransactional as i32,
-            transaction_selector: None,
-            project_id: self.project_name.clone(),
-        };
-        let request = self.construct_request(request).await?;
-        let response = self.service.commit(request).await?;
-        let response = response.into_inner();
+            .map(Mutation::put)
+            .collect::<Result<_, _>>()?;
+        let response = self.commit_mutations(mutations, None).await?;
         let keys = response
             .mutation_results
             .into_iter()
@@ -197,118 +236,427 @@ impl Client {
         I: IntoIterator<Item = T>,
         T: Borrow<Key>,
     {
-        let mutations = keys
+        let mutations = keys.into_iter().map(Mutation::delete).collect();
+        self.commit_mutations(mutations, None).await?;
+
+        Ok(())
+    }
+
+    /// Allocates IDs for incomplete keys, and returns the completed keys (in the same order).
+    ///
+    /// The namespace and ancestors of the keys are preserved.
+    pub async fn allocate_ids(&mut self, keys: Vec<Key>) -> Result<Vec<Key>, Error> {
+        let request = allocate_ids_request(self.project_name.as_str(), &keys)?;
+        let request = self.construct_request(request).await?;
+        let response = self.service.allocate_ids(request).await?;
+        let response = response.into_inner();
+
+        Ok(response.keys.into_iter().map(Key::from).collect())
+    }
+
+    /// Reserves the IDs of complete keys, so that they are never allocated by the store.
+    pub async fn reserve_ids(&mut self, keys: Vec<Key>) -> Result<(), Error> {
+        let request = reserve_ids_request(self.project_name.as_str(), &keys)?;
+        let request = self.construct_request(request).await?;
+        self.service.reserve_ids(request).await?;
+
+        Ok(())
+    }
+
+    /// Applies multiple mutations, atomically, and returns their results (in the same order).
+    pub async fn commit(&mut self, mutations: Vec<Mutation>) -> Result<Vec<MutationResult>, Error> {
+        let response = self.commit_mutations(mutations, None).await?;
+        let results = response
+            .mutation_results
             .into_iter()
-            .map(|key| convert_key(self.project_name.as_str(), key.borrow()))
-            .map(|key| api::Mutation {
-                operation: Some(api::mutation::Operation::Delete(key)),
-                conflict_detection_strategy: None,
-            })
+            .map(MutationResult::from)
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Commits mutations, within the given transaction (if any).
+    pub(crate) async fn commit_mutations(
+        &mut self,
+        mutations: Vec<Mutation>,
+        transaction: Option<Vec<u8>>,
+    ) -> Result<api::CommitResponse, Error> {
+        use api::commit_request::{Mode, TransactionSelector};
+        let mutations = mutations
+            .into_iter()
+            .map(|mutation| mutation.into_api(self.project_name.as_str()))
             .collect();
+        let mode = match transaction {
+            Some(_) => Mode::Transactional,
+            None => Mode::NonTransactional,
+        };
 
         let request = api::CommitRequest {
             mutations,
-            mode: api::commit_request::Mode::NonTransactional as i32,
-            transaction_selector: None,
+            mode: mode as i32,
+            transaction_selector: transaction.map(TransactionSelector::Transaction),
             project_id: self.project_name.clone(),
         };
         let request = self.construct_request(request).await?;
-        self.service.commit(request).await?;
+        let response = self.service.commit(request).await?;
 
-        Ok(())
+        Ok(response.into_inner())
     }
 
     /// Runs a (potentially) complex query againt Datastore and returns the results.
-    pub async fn query(&mut self, query: Query) -> Result<Vec<Entity>, Error> {
-        let mut output = Vec::new();
+    ///
+    /// Results are fetched until the query is finished or reaches its limit,
+    /// and the returned end cursor can be used to continue it later.
+    pub async fn query(&mut self, query: Query) -> Result<QueryResults, Error> {
+        self.run_query(query, None).await
+    }
 
-        let mut cur_query = query.clone();
-        let mut cursor = Vec::new();
-        loop {
-            let projection = cur_query
-                .projections
-                .into_iter()
-                .map(|name| api::Projection {
-                    property: Some(api::PropertyReference { name }),
-                })
-                .collect();
-            let filter = convert_filter(self.project_name.as_str(), cur_query.filters);
-            let order = cur_query
-                .ordering
-                .into_iter()
-                .map(|order| {
-                    use api::property_order::Direction;
-                    let (name, direction) = match order {
-                        Order::Asc(name) => (name, Direction::Ascending),
-                        Order::Desc(name) => (name, Direction::Descending),
-                    };
-                    api::PropertyOrder {
-                        property: Some(api::PropertyReference { name }),
-                        direction: direction as i32,
-                    }
-                })
-                .collect();
-            let api_query = api::Query {
-                kind: vec![api::KindExpression {
-                    name: cur_query.kind,
-                }],
-                projection,
-                filter,
-                order,
-                offset: cur_query.offset,
-                limit: cur_query.limit,
-                start_cursor: cursor,
-                end_cursor: Vec::new(),
-                distinct_on: cur_query
-                    .distinct_on
-                    .into_iter()
-                    .map(|name| api::PropertyReference { name })
-                    .collect(),
-            };
-            let request = api::RunQueryRequest {
-                partition_id: Some(api::PartitionId {
-                    project_id: self.project_name.clone(),
-                    namespace_id: cur_query.namespace.unwrap_or_else(String::new),
-                }),
-                query_type: Some(api::run_query_request::QueryType::Query(api_query)),
-                read_options: Some({
-                    use api::read_options::{ConsistencyType, ReadConsistency};
-                    api::ReadOptions {
-                        consistency_type: Some(ConsistencyType::ReadConsistency(
-                            if cur_query.eventual {
-                                ReadConsistency::Eventual as i32
-                            } else {
-                                ReadConsistency::Strong as i32
-                            },
-                        )),
-                    }
-                }),
-                project_id: self.project_name.clone(),
-            };
-            let request = self.construct_request(request).await?;
-            let results = self.service.run_query(request).await?;
-            let results = results.into_inner().batch.unwrap();
+    /// Runs a query with the given read options (instead of the query's own consistency).
+    pub async fn query_with_options(
+        &mut self,
+        query: Query,
+        options: ReadOptions,
+    ) -> Result<QueryResults, Error> {
+        self.run_query(query, Some(options.into_api())).await
+    }
+
+    /// Runs a query with the given read options (instead of the query's own consistency).
+    pub(crate) async fn run_query(
+        &mut self,
+        query: Query,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<QueryResults, Error> {
+        let request = self.query_request(query, read_options)?;
+        self.run_query_request(request).await
+    }
+
+    /// Runs a query and decodes its results, along with their keys.
+    pub async fn query_as<T>(&mut self, query: Query) -> Result<Vec<(Key, T)>, Error>
+    where
+        T: FromValue,
+    {
+        let results = self.query(query).await?;
+        decode_entities(results.entities)
+    }
+
+    /// Runs a query and lazily streams its results.
+    ///
+    /// Batches of results are only fetched when the previous ones have been consumed.
+    pub fn query_stream(&self, query: Query) -> impl Stream<Item = Result<Entity, Error>> {
+        self.stream_query(query, None)
+    }
+
+    /// Runs a query with the given read options, and lazily streams its results.
+    pub fn query_stream_with_options(
+        &self,
+        query: Query,
+        options: ReadOptions,
+    ) -> impl Stream<Item = Result<Entity, Error>> {
+        self.stream_query(query, Some(options.into_api()))
+    }
+
+    /// Runs a query and lazily streams its results, decoded along with their keys.
+    pub fn query_stream_as<T>(&self, query: Query) -> impl Stream<Item = Result<(Key, T), Error>>
+    where
+        T: FromValue,
+    {
+        self.query_stream(query).and_then(|entity| async move {
+            let value = T::from_value(entity.properties)?;
+            Ok((entity.key, value))
+        })
+    }
+
+    /// Runs a GQL query against Datastore and returns the results.
+    ///
+    /// Results are fetched until the query is finished or reaches its limit, like with `Client::query`.
+    pub async fn gql_query(&mut self, query: GqlQuery) -> Result<QueryResults, Error> {
+        self.run_gql_query(query, None).await
+    }
+
+    /// Runs a GQL query with the given read options (instead of the query's own consistency).
+    pub async fn gql_query_with_options(
+        &mut self,
+        query: GqlQuery,
+        options: ReadOptions,
+    ) -> Result<QueryResults, Error> {
+        self.run_gql_query(query, Some(options.into_api())).await
+    }
+
+    /// Runs a GQL query and lazily streams its results.
+    pub fn gql_query_stream(&self, query: GqlQuery) -> impl Stream<Item = Result<Entity, Error>> {
+        self.stream_gql_query(query, None)
+    }
+
+    /// Runs a GQL query with the given read options.
+    pub(crate) async fn run_gql_query(
+        &mut self,
+        query: GqlQuery,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<QueryResults, Error> {
+        let request = self.gql_request(query, read_options)?;
+        self.run_query_request(request).await
+    }
+
+    /// Streams the results of a query, with the given read options.
+    pub(crate) fn stream_query(
+        &self,
+        query: Query,
+        read_options: Option<api::ReadOptions>,
+    ) -> impl Stream<Item = Result<Entity, Error>> {
+        self.stream_query_request(self.query_request(query, read_options))
+    }
+
+    /// Streams the results of a GQL query, with the given read options.
+    pub(crate) fn stream_gql_query(
+        &self,
+        query: GqlQuery,
+        read_options: Option<api::ReadOptions>,
+    ) -> impl Stream<Item = Result<Entity, Error>> {
+        self.stream_query_request(self.gql_request(query, read_options))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn query_request(
+        &self,
+        query: Query,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<QueryRequest, Error> {
+        query.validate()?;
+        let read_options = read_options.unwrap_or_else(|| convert_consistency(query.eventual));
+        Ok(QueryRequest {
+            query_type: QueryType::Query(convert_query(self.project_name.as_str(), &query)),
+            namespace: query.namespace.unwrap_or_default(),
+            read_options,
+        })
+    }
 
-            output.extend(
-                results
+    #[allow(clippy::result_large_err)]
+    fn gql_request(
+        &self,
+        query: GqlQuery,
+        read_options: Option<api::ReadOptions>,
+    ) -> Result<QueryRequest, Error> {
+        query.validate()?;
+        let read_options = read_options.unwrap_or_else(|| convert_consistency(query.eventual));
+        Ok(QueryRequest {
+            namespace: query.namespace.clone().unwrap_or_default(),
+            query_type: QueryType::GqlQuery(query.into_api(self.project_name.as_str())),
+            read_options,
+        })
+    }
+
+    async fn run_query_request(
+        &mut self,
+        mut request: QueryRequest,
+    ) -> Result<QueryResults, Error> {
+        let mut output = QueryResults {
+            entities: Vec::new(),
+            end_cursor: Vec::new(),
+            more_results: MoreResults::NotFinished,
+            skipped_results: 0,
+        };
+
+        loop {
+            let response = self.run_query_batch(&request).await?;
+            let batch = response.batch.unwrap();
+            let skipped = batch.skipped_results;
+            let returned = batch.entity_results.len();
+
+            output.skipped_results += skipped;
+            output.entities.extend(
+                batch
                     .entity_results
                     .into_iter()
                     .map(|el| Entity::from(el.entity.unwrap())),
             );
+            output.end_cursor = batch.end_cursor;
+            output.more_results = MoreResults::from(batch.more_results);
 
-            if results.more_results
-                != (api::query_result_batch::MoreResultsType::NotFinished as i32)
-            {
+            if output.more_results != MoreResults::NotFinished {
                 break Ok(output);
             }
+            request.advance(response.query, skipped, returned, output.end_cursor.clone());
+        }
+    }
 
-            cur_query = query.clone();
-            cursor = results.end_cursor;
+    fn stream_query_request(
+        &self,
+        request: Result<QueryRequest, Error>,
+    ) -> impl Stream<Item = Result<Entity, Error>> {
+        let state = Some((self.clone(), request));
+        stream::try_unfold(state, |state| async move {
+            let (mut client, request) = match state {
+                Some(state) => state,
+                None => return Ok::<_, Error>(None),
+            };
+            let mut request = request?;
+            let response = client.run_query_batch(&request).await?;
+            let batch = response.batch.unwrap();
+            let entities: Vec<Entity> = batch
+                .entity_results
+                .into_iter()
+                .map(|el| Entity::from(el.entity.unwrap()))
+                .collect();
+
+            let next = if MoreResults::from(batch.more_results) == MoreResults::NotFinished {
+                let skipped = batch.skipped_results;
+                request.advance(response.query, skipped, entities.len(), batch.end_cursor);
+                Some((client, Ok(request)))
+            } else {
+                None
+            };
+            Ok(Some((entities, next)))
+        })
+        .map_ok(|entities| stream::iter(entities.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Runs a query and returns a single batch of results.
+    async fn run_query_batch(
+        &mut self,
+        request: &QueryRequest,
+    ) -> Result<api::RunQueryResponse, Error> {
+        let request = api::RunQueryRequest {
+            partition_id: Some(api::PartitionId {
+                project_id: self.project_name.clone(),
+                namespace_id: request.namespace.clone(),
+            }),
+            query_type: Some(request.query_type.clone()),
+            read_options: Some(request.read_options.clone()),
+            project_id: self.project_name.clone(),
+        };
+        let request = self.construct_request(request).await?;
+        let response = self.service.run_query(request).await?;
+
+        Ok(response.into_inner())
+    }
+}
+
+/// A query to run, along with where and how to run it.
+#[derive(Debug, Clone)]
+struct QueryRequest {
+    query_type: QueryType,
+    namespace: String,
+    read_options: api::ReadOptions,
+}
+
+impl QueryRequest {
+    /// Moves the request past a batch of results, to fetch the next one.
+    ///
+    /// GQL queries continue in their parsed form, which is returned along with their results.
+    fn advance(
+        &mut self,
+        parsed: Option<api::Query>,
+        skipped: i32,
+        returned: usize,
+        cursor: Vec<u8>,
+    ) {
+        if let (QueryType::GqlQuery(_), Some(parsed)) = (&self.query_type, parsed) {
+            self.query_type = QueryType::Query(parsed);
         }
+        if let QueryType::Query(query) = &mut self.query_type {
+            query.advance(skipped, returned, cursor);
+        }
+    }
+}
+
+/// Sorts looked up entities and missing keys in the order of the requested keys.
+///
+/// Keys are compared the way the store sees them, since the ones it returns carry
+/// their namespace on every ancestor, unlike the requested ones.
+#[allow(clippy::result_large_err)]
+pub(crate) fn lookup_results<T, K>(
+    project_name: &str,
+    keys: &[K],
+    found: HashMap<Key, Value>,
+    missing: HashSet<Key>,
+    deferred: Vec<Key>,
+) -> Result<LookupResults<T>, Error>
+where
+    K: Borrow<Key>,
+    T: FromValue,
+{
+    let mut results = LookupResults {
+        found: Vec::new(),
+        missing: Vec::new(),
+        deferred,
+    };
+    let normalize = |key: &Key| Key::from(convert_key(project_name, key));
+    let found: HashMap<Key, Value> = found
+        .into_iter()
+        .map(|(key, value)| (normalize(&key), value))
+        .collect();
+    let missing: HashSet<Key> = missing.iter().map(normalize).collect();
+    for key in keys {
+        let key = key.borrow();
+        let normalized = normalize(key);
+        if let Some(value) = found.get(&normalized) {
+            let value = T::from_value(value.clone())?;
+            results.found.push((key.clone(), value));
+        } else if missing.contains(&normalized) {
+            results.missing.push(key.clone());
+        }
+    }
+
+    Ok(results)
+}
+
+/// Decodes the properties of entities, keeping them along with their keys.
+#[allow(clippy::result_large_err)]
+pub(crate) fn decode_entities<T>(entities: Vec<Entity>) -> Result<Vec<(Key, T)>, Error>
+where
+    T: FromValue,
+{
+    entities
+        .into_iter()
+        .map(|entity| Ok((entity.key, T::from_value(entity.properties)?)))
+        .collect()
+}
+
+/// Builds the request allocating IDs for incomplete keys.
+#[allow(clippy::result_large_err)]
+pub(crate) fn allocate_ids_request(
+    project_name: &str,
+    keys: &[Key],
+) -> Result<api::AllocateIdsRequest, Error> {
+    if keys.iter().any(|key| !key.is_incomplete()) {
+        return Err(Error::InvalidArgument(String::from(
+            "only incomplete keys can have IDs allocated",
+        )));
+    }
+
+    Ok(api::AllocateIdsRequest {
+        project_id: String::from(project_name),
+        keys: keys
+            .iter()
+            .map(|key| convert_key(project_name, key))
+            .collect(),
+    })
+}
+
+/// Builds the request reserving the IDs of complete keys.
+#[allow(clippy::result_large_err)]
+pub(crate) fn reserve_ids_request(
+    project_name: &str,
+    keys: &[Key],
+) -> Result<api::ReserveIdsRequest, Error> {
+    if keys.iter().any(Key::is_incomplete) {
+        return Err(Error::InvalidArgument(String::from(
+            "only complete keys can have their IDs reserved",
+        )));
     }
+
+    Ok(api::ReserveIdsRequest {
+        project_id: String::from(project_name),
+        keys: keys
+            .iter()
+            .map(|key| convert_key(project_name, key))
+            .collect(),
+        ..Default::default()
+    })
 }
 
-fn convert_key(project_name: &str, key: &Key) -> api::Key {
+pub(crate) fn convert_key(project_name: &str, key: &Key) -> api::Key {
     api::Key {
         partition_id: Some(api::PartitionId {
             project_id: String::from(project_name),
@@ -336,7 +684,7 @@ fn convert_key(project_name: &str, key: &Key) -> api::Key {
     }
 }
 
-fn convert_entity(project_name: &str, entity: Entity) -> api::Entity {
+pub(crate) fn convert_entity(project_name: &str, entity: Entity) -> api::Entity {
     let key = convert_key(project_name, &entity.key);
     let properties = match entity.properties {
         Value::EntityValue(properties) => properties,
@@ -352,15 +700,20 @@ fn convert_entity(project_name: &str, entity: Entity) -> api::Entity {
     }
 }
 
-fn convert_value(project_name: &str, value: Value) -> api::Value {
+pub(crate) fn convert_timestamp(time: NaiveDateTime) -> prost_types::Timestamp {
+    let time = time.and_utc();
+    prost_types::Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+pub(crate) fn convert_value(project_name: &str, value: Value) -> api::Value {
     let value_type = match value {
         Value::BooleanValue(val) => ValueType::BooleanValue(val),
         Value::IntegerValue(val) => ValueType::IntegerValue(val),
         Value::DoubleValue(val) => ValueType::DoubleValue(val),
-        Value::TimestampValue(val) => ValueType::TimestampValue(prost_types::Timestamp {
-            seconds: val.timestamp(),
-            nanos: val.timestamp_subsec_nanos() as i32,
-        }),
+        Value::TimestampValue(val) => ValueType::TimestampValue(convert_timestamp(val)),
         Value::KeyValue(key) => ValueType::KeyValue(convert_key(project_name, &key)),
         Value::StringValue(val) => ValueType::StringValue(val),
         Value::BlobValue(val) => ValueType::BlobValue(val),
@@ -391,43 +744,137 @@ fn convert_value(project_name: &str, value: Value) -> api::Value {
     }
 }
 
+pub(crate) fn convert_consistency(eventual: bool) -> api::ReadOptions {
+    let options = if eventual {
+        ReadOptions::Eventual
+    } else {
+        ReadOptions::Strong
+    };
+    options.into_api()
+}
+
+pub(crate) fn convert_query(project_name: &str, query: &Query) -> api::Query {
+    let projection = if query.keys_only && query.projections.is_empty() {
+        vec![String::from("__key__")]
+    } else {
+        query.projections.clone()
+    };
+    let projection = projection
+        .into_iter()
+        .map(|name| api::Projection {
+            property: Some(api::PropertyReference { name }),
+        })
+        .collect();
+    let mut filters = query.filters.clone();
+    if let Some(ancestor) = query.ancestor.clone() {
+        filters.push(Filter::HasAncestor(ancestor));
+    }
+    let order = query
+        .ordering
+        .iter()
+        .map(|order| {
+            use api::property_order::Direction;
+            let (name, direction) = match order {
+                Order::Asc(name) => (name.clone(), Direction::Ascending),
+                Order::Desc(name) => (name.clone(), Direction::Descending),
+            };
+            api::PropertyOrder {
+                property: Some(api::PropertyReference { name }),
+                direction: direction as i32,
+            }
+        })
+        .collect();
+
+    api::Query {
+        kind: vec![api::KindExpression {
+            name: query.kind.clone(),
+        }],
+        projection,
+        filter: convert_filter(project_name, filters),
+        order,
+        offset: query.offset,
+        limit: query.limit,
+        start_cursor: query.start_cursor.clone().unwrap_or_default(),
+        end_cursor: query.end_cursor.clone().unwrap_or_default(),
+        distinct_on: query
+            .distinct_on
+            .iter()
+            .map(|name| api::PropertyReference { name: name.clone() })
+            .collect(),
+    }
+}
+
 fn convert_filter(project_name: &str, filters: Vec<Filter>) -> Option<api::Filter> {
+    let mut filters = filters;
+    match filters.len() {
+        0 => None,
+        1 => Some(convert_filter_tree(project_name, filters.remove(0))),
+        _ => Some(convert_filter_tree(project_name, Filter::And(filters))),
+    }
+}
+
+fn convert_filter_tree(project_name: &str, filter: Filter) -> api::Filter {
     use api::filter::FilterType;
+    use api::property_filter::Operator;
 
-    if !filters.is_empty() {
-        let filters = filters
-            .into_iter()
-            .map(|filter| {
-                use api::property_filter::Operator;
-                let (name, op, value) = match filter {
-                    Filter::Equal(name, value) => (name, Operator::Equal, value),
-                    Filter::GreaterThan(name, value) => (name, Operator::GreaterThan, value),
-                    Filter::LesserThan(name, value) => (name, Operator::LessThan, value),
-                    Filter::GreaterThanOrEqual(name, value) => {
-                        (name, Operator::GreaterThanOrEqual, value)
-                    }
-                    Filter::LesserThanEqual(name, value) => {
-                        (name, Operator::LessThanOrEqual, value)
-                    }
-                };
-
-                api::Filter {
-                    filter_type: Some(FilterType::PropertyFilter(api::PropertyFilter {
-                        op: op as i32,
-                        property: Some(api::PropertyReference { name }),
-                        value: Some(convert_value(project_name, value)),
-                    })),
-                }
-            })
-            .collect();
+    let (name, op, value) = match filter {
+        Filter::And(filters) | Filter::Or(filters) if filters.len() == 1 => {
+            return convert_filter_tree(project_name, filters.into_iter().next().unwrap());
+        }
+        Filter::And(filters) => {
+            return convert_composite_filter(
+                project_name,
+                api::composite_filter::Operator::And,
+                filters,
+            );
+        }
+        Filter::Or(filters) => {
+            return convert_composite_filter(
+                project_name,
+                api::composite_filter::Operator::Or,
+                filters,
+            );
+        }
+        Filter::Equal(name, value) => (name, Operator::Equal, value),
+        Filter::GreaterThan(name, value) => (name, Operator::GreaterThan, value),
+        Filter::LesserThan(name, value) => (name, Operator::LessThan, value),
+        Filter::GreaterThanOrEqual(name, value) => (name, Operator::GreaterThanOrEqual, value),
+        Filter::LesserThanEqual(name, value) => (name, Operator::LessThanOrEqual, value),
+        Filter::NotEqual(name, value) => (name, Operator::NotEqual, value),
+        Filter::In(name, values) => (name, Operator::In, Value::ArrayValue(values)),
+        Filter::NotIn(name, values) => (name, Operator::NotIn, Value::ArrayValue(values)),
+        Filter::HasAncestor(key) => (
+            String::from("__key__"),
+            Operator::HasAncestor,
+            Value::KeyValue(key),
+        ),
+    };
 
-        Some(api::Filter {
-            filter_type: Some(FilterType::CompositeFilter(api::CompositeFilter {
-                op: api::composite_filter::Operator::And as i32,
+    api::Filter {
+        filter_type: Some(FilterType::PropertyFilter(api::PropertyFilter {
+            op: op as i32,
+            property: Some(api::PropertyReference { name }),
+            value: Some(convert_value(project_name, value)),
+        })),
+    }
+}
+
+fn convert_composite_filter(
+    project_name: &str,
+    op: api::composite_filter::Operator,
+    filters: Vec<Filter>,
+) -> api::Filter {
+    let filters = filters
+        .into_iter()
+        .map(|filter| convert_filter_tree(project_name, filter))
+        .collect();
+
+    api::Filter {
+        filter_type: Some(api::filter::FilterType::CompositeFilter(
+            api::CompositeFilter {
+                op: op as i32,
                 filters,
-            })),
-        })
-    } else {
-        None
+            },
+        )),
     }
 }
@@ -85,3 +85,14 @@ impl From<api::Entity> for Entity {
         Entity { key, properties }
     }
 }
+
+/// The results of looking up multiple entities from their keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupResults<T> {
+    /// The found entities, along with their keys.
+    pub found: Vec<(Key, T)>,
+    /// The keys of the entities that were not found.
+    pub missing: Vec<Key>,
+    /// The keys that the store kept deferring, and that were not looked up.
+    pub deferred: Vec<Key>,
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use crate::datastore::api;
+use crate::datastore::api::gql_query_parameter::ParameterType;
+use crate::datastore::client::convert_value;
+use crate::datastore::{Error, IntoValue, Value};
+
+/// Represents a parameter bound to a GQL query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GqlParameter {
+    /// A value parameter.
+    Value(Value),
+    /// A query cursor parameter (like the `end_cursor` of previous results).
+    Cursor(Vec<u8>),
+}
+
+/// Represents a Datastore query written in GQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GqlQuery {
+    pub(crate) query: String,
+    pub(crate) allow_literals: bool,
+    pub(crate) eventual: bool,
+    pub(crate) namespace: Option<String>,
+    pub(crate) named_bindings: HashMap<String, GqlParameter>,
+    pub(crate) positional_bindings: Vec<GqlParameter>,
+}
+
+impl GqlQuery {
+    /// Construct a new GQL query.
+    ///
+    /// ```
+    /// # use google_cloud::datastore::GqlQuery;
+    /// let query = GqlQuery::new("SELECT * FROM users WHERE age >= @age AND country = @1")
+    ///     .bind("age", 18)
+    ///     .bind_positional("FR");
+    /// ```
+    pub fn new(query: impl Into<String>) -> GqlQuery {
+        GqlQuery {
+            query: query.into(),
+            allow_literals: false,
+            eventual: false,
+            namespace: None,
+            named_bindings: HashMap::new(),
+            positional_bindings: Vec::new(),
+        }
+    }
+
+    /// Allow values to be written directly in the query string, instead of only through bindings.
+    pub fn allow_literals(mut self, value: bool) -> GqlQuery {
+        self.allow_literals = value;
+        self
+    }
+
+    /// Ask to accept eventually consistent results.
+    pub fn eventually_consistent(mut self) -> GqlQuery {
+        self.eventual = true;
+        self
+    }
+
+    /// Associates the query with a namespace.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> GqlQuery {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Binds a value to a named parameter (referenced as `@name` in the query).
+    pub fn bind(mut self, name: impl Into<String>, value: impl IntoValue) -> GqlQuery {
+        let value = GqlParameter::Value(value.into_value());
+        self.named_bindings.insert(name.into(), value);
+        self
+    }
+
+    /// Binds a cursor to a named parameter (referenced as `@name` in the query).
+    pub fn bind_cursor(mut self, name: impl Into<String>, cursor: impl Into<Vec<u8>>) -> GqlQuery {
+        let cursor = GqlParameter::Cursor(cursor.into());
+        self.named_bindings.insert(name.into(), cursor);
+        self
+    }
+
+    /// Binds a value to the next positional parameter (referenced as `@1`, `@2`, ... in the query).
+    pub fn bind_positional(mut self, value: impl IntoValue) -> GqlQuery {
+        let value = GqlParameter::Value(value.into_value());
+        self.positional_bindings.push(value);
+        self
+    }
+
+    /// Binds a cursor to the next positional parameter (referenced as `@1`, `@2`, ... in the query).
+    pub fn bind_positional_cursor(mut self, cursor: impl Into<Vec<u8>>) -> GqlQuery {
+        let cursor = GqlParameter::Cursor(cursor.into());
+        self.positional_bindings.push(cursor);
+        self
+    }
+
+    /// Checks the query bindings before sending them.
+    ///
+    /// Binding names must be identifiers (letters, digits, `_` or `$`, not starting with a digit),
+    /// and must not be surrounded by double underscores.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.query.trim().is_empty() {
+            return Err(Error::InvalidArgument(String::from(
+                "GQL queries must not be empty",
+            )));
+        }
+        for name in self.named_bindings.keys() {
+            let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+            let valid = name.chars().all(is_ident_char)
+                && name.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && !(name.len() >= 4 && name.starts_with("__") && name.ends_with("__"));
+            if !valid {
+                return Err(Error::InvalidArgument(format!(
+                    "invalid GQL binding name: `{}`",
+                    name,
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_api(self, project_name: &str) -> api::GqlQuery {
+        let convert = |parameter| {
+            let parameter_type = match parameter {
+                GqlParameter::Value(value) => {
+                    ParameterType::Value(convert_value(project_name, value))
+                }
+                GqlParameter::Cursor(cursor) => ParameterType::Cursor(cursor),
+            };
+            api::GqlQueryParameter {
+                parameter_type: Some(parameter_type),
+            }
+        };
+
+        api::GqlQuery {
+            query_string: self.query,
+            allow_literals: self.allow_literals,
+            named_bindings: self
+                .named_bindings
+                .into_iter()
+                .map(|(name, parameter)| (name, convert(parameter)))
+                .collect(),
+            positional_bindings: self.positional_bindings.into_iter().map(convert).collect(),
+        }
+    }
+}
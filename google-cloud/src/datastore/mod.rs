@@ -1,9 +1,15 @@
+mod aggregation;
 mod client;
 mod entity;
+mod gql;
 mod key;
+mod mutation;
+mod options;
 mod query;
+mod transaction;
 mod value;
-mod api {
+#[allow(clippy::all)]
+pub(crate) mod api {
     pub mod r#type {
         include!("api/google.r#type.rs");
     }
@@ -16,10 +22,15 @@ mod api {
     pub use self::r#type::*;
 }
 
+pub use self::aggregation::*;
 pub use self::client::*;
 pub use self::entity::*;
+pub use self::gql::*;
 pub use self::key::*;
+pub use self::mutation::*;
+pub use self::options::*;
 pub use self::query::*;
+pub use self::transaction::*;
 pub use self::value::*;
 
 /// The error type for the Datastore module.
@@ -1,13 +1,131 @@
+use std::borrow::Borrow;
+
 use crate::datastore::api;
-use crate::datastore::{Key, Value};
+use crate::datastore::client::{convert_entity, convert_key};
+use crate::datastore::{Entity, Error, IntoEntity, Key};
 
-/// Represents a Datastore mutation operation.
+/// The write operation of a mutation.
 #[derive(Debug, Clone, PartialEq)]
-pub enum Mutation {
+pub enum Operation {
+    /// Inserts a new entity (fails if it already exists).
     Insert(Entity),
+    /// Updates an existing entity (fails if it does not exist).
     Update(Entity),
+    /// Inserts or replaces an entity.
     Upsert(Entity),
+    /// Deletes an entity (which may or may not exist).
     Delete(Key),
 }
 
-impl Mutation {}
+/// Represents a Datastore mutation operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mutation {
+    pub(crate) operation: Operation,
+    pub(crate) base_version: Option<i64>,
+}
+
+impl Mutation {
+    /// Creates a mutation inserting a new entity.
+    #[allow(clippy::result_large_err)]
+    pub fn insert(entity: impl IntoEntity) -> Result<Mutation, Error> {
+        Ok(Mutation::from(Operation::Insert(entity.into_entity()?)))
+    }
+
+    /// Creates a mutation updating an existing entity.
+    #[allow(clippy::result_large_err)]
+    pub fn update(entity: impl IntoEntity) -> Result<Mutation, Error> {
+        Ok(Mutation::from(Operation::Update(entity.into_entity()?)))
+    }
+
+    /// Creates a mutation inserting or replacing an entity.
+    #[allow(clippy::result_large_err)]
+    pub fn upsert(entity: impl IntoEntity) -> Result<Mutation, Error> {
+        Ok(Mutation::from(Operation::Upsert(entity.into_entity()?)))
+    }
+
+    /// Creates a mutation like `Client::put`: inserts entities with incomplete keys, upserts the others.
+    #[allow(clippy::result_large_err)]
+    pub fn put(entity: impl IntoEntity) -> Result<Mutation, Error> {
+        let entity = entity.into_entity()?;
+        let operation = if entity.key.is_incomplete() {
+            Operation::Insert(entity)
+        } else {
+            Operation::Upsert(entity)
+        };
+        Ok(Mutation::from(operation))
+    }
+
+    /// Creates a mutation deleting the entity identified by a key.
+    pub fn delete(key: impl Borrow<Key>) -> Mutation {
+        Mutation::from(Operation::Delete(key.borrow().clone()))
+    }
+
+    /// Only applies the mutation if the entity's current version on the server is `version`.
+    ///
+    /// Conflicting mutations are not applied, and are reported as such in their `MutationResult`.
+    pub fn base_version(mut self, version: i64) -> Mutation {
+        self.base_version = Some(version);
+        self
+    }
+
+    /// The write operation of this mutation.
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// The key of the entity affected by this mutation.
+    pub fn key(&self) -> &Key {
+        match &self.operation {
+            Operation::Insert(entity) | Operation::Update(entity) | Operation::Upsert(entity) => {
+                &entity.key
+            }
+            Operation::Delete(key) => key,
+        }
+    }
+
+    pub(crate) fn into_api(self, project_name: &str) -> api::Mutation {
+        use api::mutation::{ConflictDetectionStrategy, Operation as ApiOperation};
+        let operation = match self.operation {
+            Operation::Insert(entity) => ApiOperation::Insert(convert_entity(project_name, entity)),
+            Operation::Update(entity) => ApiOperation::Update(convert_entity(project_name, entity)),
+            Operation::Upsert(entity) => ApiOperation::Upsert(convert_entity(project_name, entity)),
+            Operation::Delete(key) => ApiOperation::Delete(convert_key(project_name, &key)),
+        };
+        api::Mutation {
+            operation: Some(operation),
+            conflict_detection_strategy: self
+                .base_version
+                .map(ConflictDetectionStrategy::BaseVersion),
+        }
+    }
+}
+
+impl From<Operation> for Mutation {
+    fn from(operation: Operation) -> Mutation {
+        Mutation {
+            operation,
+            base_version: None,
+        }
+    }
+}
+
+/// The result of applying a mutation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutationResult {
+    /// The key generated by the store, for an inserted entity with an incomplete key.
+    pub key: Option<Key>,
+    /// The version of the entity on the server after processing the mutation.
+    pub version: i64,
+    /// Whether a conflict with the mutation's base version was detected (the mutation was not applied).
+    pub conflict_detected: bool,
+}
+
+impl From<api::MutationResult> for MutationResult {
+    fn from(result: api::MutationResult) -> MutationResult {
+        MutationResult {
+            key: result.key.map(Key::from),
+            version: result.version,
+            conflict_detected: result.conflict_detected,
+        }
+    }
+}
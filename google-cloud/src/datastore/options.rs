@@ -0,0 +1,34 @@
+use chrono::NaiveDateTime;
+
+use crate::datastore::api;
+use crate::datastore::api::read_options::{ConsistencyType, ReadConsistency};
+use crate::datastore::client::convert_timestamp;
+
+/// Represents how entities are read, by lookups and queries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadOptions {
+    /// Read the latest committed version of entities.
+    Strong,
+    /// Accept eventually consistent (possibly stale) results, with lower latency.
+    Eventual,
+    /// Read within a transaction, from its identifier (like `Transaction::id`).
+    Transaction(Vec<u8>),
+    /// Read entities as they were at the given time (up to an hour ago, or more with point-in-time recovery).
+    ReadTime(NaiveDateTime),
+}
+
+impl ReadOptions {
+    pub(crate) fn into_api(self) -> api::ReadOptions {
+        let consistency_type = match self {
+            ReadOptions::Strong => ConsistencyType::ReadConsistency(ReadConsistency::Strong as i32),
+            ReadOptions::Eventual => {
+                ConsistencyType::ReadConsistency(ReadConsistency::Eventual as i32)
+            }
+            ReadOptions::Transaction(id) => ConsistencyType::Transaction(id),
+            ReadOptions::ReadTime(time) => ConsistencyType::ReadTime(convert_timestamp(time)),
+        };
+        api::ReadOptions {
+            consistency_type: Some(consistency_type),
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use crate::datastore::{Key, Value};
+use crate::datastore::api;
+use crate::datastore::{Entity, Error, IntoValue, Key, Value};
 
 /// Represents Datastore query result orderings.
 #[derive(Debug, Clone, PartialEq)]
@@ -9,7 +10,7 @@ pub enum Order {
     Desc(String),
 }
 
-/// Represents Datastore query result orderings.
+/// Represents Datastore query filters.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Filter {
     /// Equality filter (==).
@@ -22,6 +23,163 @@ pub enum Filter {
     GreaterThanOrEqual(String, Value),
     /// Lesser-than-or-equal filter (<=).
     LesserThanEqual(String, Value),
+    /// Inequality filter (!=).
+    NotEqual(String, Value),
+    /// Membership filter (the property is equal to one of the values).
+    In(String, Vec<Value>),
+    /// Non-membership filter (the property is equal to none of the values).
+    NotIn(String, Vec<Value>),
+    /// Ancestor filter (the entity is the given one or one of its descendants).
+    HasAncestor(Key),
+    /// Conjunction of filters (all of them must match).
+    And(Vec<Filter>),
+    /// Disjunction of filters (at least one of them must match).
+    Or(Vec<Filter>),
+}
+
+impl Filter {
+    /// The maximum number of values of an `In` or `NotIn` filter.
+    pub const MAX_VALUES: usize = 10;
+
+    /// Starts a filter on a property.
+    ///
+    /// ```
+    /// # use google_cloud::datastore::Filter;
+    /// let filter = Filter::prop("age")
+    ///     .gt(10)
+    ///     .or(Filter::prop("firstname").is_in(vec!["john", "jane"]));
+    /// ```
+    pub fn prop(name: impl Into<String>) -> FilterProperty {
+        FilterProperty { name: name.into() }
+    }
+
+    /// Combines this filter with another one, both of them must match.
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// Combines this filter with another one, at least one of them must match.
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Is this filter an inequality on a property (including `!=` and `NOT IN`) ?
+    fn inequality_property(&self) -> Option<&str> {
+        match self {
+            Filter::GreaterThan(name, _)
+            | Filter::LesserThan(name, _)
+            | Filter::GreaterThanOrEqual(name, _)
+            | Filter::LesserThanEqual(name, _)
+            | Filter::NotEqual(name, _)
+            | Filter::NotIn(name, _) => Some(name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Checks the filter tree, and collects the property filters it contains.
+    #[allow(clippy::result_large_err)]
+    fn check<'a>(&'a self, leaves: &mut Vec<&'a Filter>) -> Result<(), Error> {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) => {
+                if filters.is_empty() {
+                    return Err(Error::InvalidArgument(String::from(
+                        "composite filters must contain at least one filter",
+                    )));
+                }
+                for filter in filters {
+                    filter.check(leaves)?;
+                }
+            }
+            Filter::In(name, values) | Filter::NotIn(name, values) => {
+                if values.is_empty() || values.len() > Filter::MAX_VALUES {
+                    return Err(Error::InvalidArgument(format!(
+                        "membership filters on `{}` must have between 1 and {} values",
+                        name,
+                        Filter::MAX_VALUES,
+                    )));
+                }
+                leaves.push(self);
+            }
+            Filter::HasAncestor(key) => {
+                if key.is_incomplete() {
+                    return Err(Error::InvalidArgument(String::from(
+                        "ancestor filters require a complete key",
+                    )));
+                }
+                leaves.push(self);
+            }
+            _ => leaves.push(self),
+        }
+        Ok(())
+    }
+}
+
+/// A property to build a filter on, see `Filter::prop`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterProperty {
+    name: String,
+}
+
+impl FilterProperty {
+    /// The property is equal to the value.
+    pub fn eq(self, value: impl IntoValue) -> Filter {
+        Filter::Equal(self.name, value.into_value())
+    }
+
+    /// The property is not equal to the value.
+    pub fn ne(self, value: impl IntoValue) -> Filter {
+        Filter::NotEqual(self.name, value.into_value())
+    }
+
+    /// The property is greater than the value.
+    pub fn gt(self, value: impl IntoValue) -> Filter {
+        Filter::GreaterThan(self.name, value.into_value())
+    }
+
+    /// The property is greater than or equal to the value.
+    pub fn gte(self, value: impl IntoValue) -> Filter {
+        Filter::GreaterThanOrEqual(self.name, value.into_value())
+    }
+
+    /// The property is lesser than the value.
+    pub fn lt(self, value: impl IntoValue) -> Filter {
+        Filter::LesserThan(self.name, value.into_value())
+    }
+
+    /// The property is lesser than or equal to the value.
+    pub fn lte(self, value: impl IntoValue) -> Filter {
+        Filter::LesserThanEqual(self.name, value.into_value())
+    }
+
+    /// The property is equal to one of the values.
+    pub fn is_in<T, I>(self, values: I) -> Filter
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoValue,
+    {
+        Filter::In(self.name, values.into_iter().map(T::into_value).collect())
+    }
+
+    /// The property is equal to none of the values.
+    pub fn not_in<T, I>(self, values: I) -> Filter
+    where
+        I: IntoIterator<Item = T>,
+        T: IntoValue,
+    {
+        Filter::NotIn(self.name, values.into_iter().map(T::into_value).collect())
+    }
 }
 
 /// Represents a Datastore query.
@@ -38,6 +196,8 @@ pub struct Query {
     pub(crate) distinct_on: Vec<String>,
     pub(crate) ordering: Vec<Order>,
     pub(crate) filters: Vec<Filter>,
+    pub(crate) start_cursor: Option<Vec<u8>>,
+    pub(crate) end_cursor: Option<Vec<u8>>,
 }
 
 impl Query {
@@ -60,6 +220,8 @@ impl Query {
             distinct_on: Vec::new(),
             ordering: Vec::new(),
             filters: Vec::new(),
+            start_cursor: None,
+            end_cursor: None,
         }
     }
 
@@ -183,13 +345,37 @@ impl Query {
     ///
     /// let query = Query::new("users")
     ///     .filter(Filter::GreaterThan("age".into(), 10.into_value()))
-    ///     .filter(Filter::Equal("firstname".into(), "john".into_value()));
+    ///     .filter(Filter::prop("firstname").eq("john").or(Filter::prop("lastname").eq("doe")));
     /// ```
     pub fn filter(mut self, filter: Filter) -> Query {
         self.filters.push(filter);
         self
     }
 
+    /// Start yielding results from a cursor (like the `end_cursor` of previous results).
+    ///
+    /// ```
+    /// # use google_cloud::datastore::Query;
+    /// # let cursor: Vec<u8> = Vec::new();
+    /// let query = Query::new("users").start_cursor(cursor).limit(25);
+    /// ```
+    pub fn start_cursor(mut self, cursor: impl Into<Vec<u8>>) -> Query {
+        self.start_cursor = Some(cursor.into());
+        self
+    }
+
+    /// Stop yielding results at a cursor.
+    ///
+    /// ```
+    /// # use google_cloud::datastore::Query;
+    /// # let cursor: Vec<u8> = Vec::new();
+    /// let query = Query::new("users").end_cursor(cursor);
+    /// ```
+    pub fn end_cursor(mut self, cursor: impl Into<Vec<u8>>) -> Query {
+        self.end_cursor = Some(cursor.into());
+        self
+    }
+
     /// Order results based on some of their fields.
     /// Multiple orderings are applied in the order they are added.
     ///
@@ -206,3 +392,127 @@ impl Query {
         self
     }
 }
+
+impl Query {
+    /// Checks the query filters before sending them.
+    ///
+    /// Queries can use inequalities on at most one property, which must also come first in the ordering,
+    /// and at most one `NotEqual` or `NotIn` filter and one `In` or `NotIn` filter.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let mut leaves = Vec::new();
+        if self.ancestor.as_ref().is_some_and(Key::is_incomplete) {
+            return Err(Error::InvalidArgument(String::from(
+                "ancestor filters require a complete key",
+            )));
+        }
+        for filter in self.filters.iter() {
+            filter.check(&mut leaves)?;
+        }
+
+        let mut inequality: Option<&str> = None;
+        for name in leaves
+            .iter()
+            .filter_map(|filter| filter.inequality_property())
+        {
+            match inequality {
+                Some(prev) if prev != name => {
+                    return Err(Error::InvalidArgument(format!(
+                        "inequality filters are on multiple properties (`{}` and `{}`)",
+                        prev, name,
+                    )));
+                }
+                _ => inequality = Some(name),
+            }
+        }
+        let negations = leaves
+            .iter()
+            .filter(|filter| matches!(filter, Filter::NotEqual(..) | Filter::NotIn(..)))
+            .count();
+        let memberships = leaves
+            .iter()
+            .filter(|filter| matches!(filter, Filter::In(..) | Filter::NotIn(..)))
+            .count();
+        if negations > 1 || memberships > 1 {
+            return Err(Error::InvalidArgument(String::from(
+                "queries can have at most one `NotEqual`/`NotIn` and one `In`/`NotIn` filter",
+            )));
+        }
+
+        if let (Some(name), Some(order)) = (inequality, self.ordering.first()) {
+            let first = match order {
+                Order::Asc(first) | Order::Desc(first) => first,
+            };
+            if first != name {
+                return Err(Error::InvalidArgument(format!(
+                    "the inequality property `{}` must be ordered first",
+                    name,
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of a query after a batch of results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoreResults {
+    /// There may be more results after the end cursor.
+    NotFinished,
+    /// The query stopped because of its limit, there may be more results after the end cursor.
+    MoreResultsAfterLimit,
+    /// The query stopped at its end cursor, there may be more results after it.
+    MoreResultsAfterCursor,
+    /// The query is finished, there are no more results.
+    NoMoreResults,
+}
+
+impl From<i32> for MoreResults {
+    fn from(value: i32) -> MoreResults {
+        use api::query_result_batch::MoreResultsType;
+        match value {
+            x if x == MoreResultsType::MoreResultsAfterLimit as i32 => {
+                MoreResults::MoreResultsAfterLimit
+            }
+            x if x == MoreResultsType::MoreResultsAfterCursor as i32 => {
+                MoreResults::MoreResultsAfterCursor
+            }
+            x if x == MoreResultsType::NoMoreResults as i32 => MoreResults::NoMoreResults,
+            _ => MoreResults::NotFinished,
+        }
+    }
+}
+
+/// The results of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResults {
+    /// The resulting entities (only with their keys for keys-only queries).
+    pub entities: Vec<Entity>,
+    /// A cursor pointing after the last result, to continue the query from with `Query::start_cursor`.
+    pub end_cursor: Vec<u8>,
+    /// Whether there may be more results after the end cursor.
+    pub more_results: MoreResults,
+    /// The number of results skipped because of the query's offset.
+    pub skipped_results: i32,
+}
+
+impl IntoIterator for QueryResults {
+    type Item = Entity;
+    type IntoIter = std::vec::IntoIter<Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entities.into_iter()
+    }
+}
+
+impl api::Query {
+    /// Moves the query past a batch of results, to fetch the next one.
+    ///
+    /// The offset and limit are reduced by what the batch already skipped and returned.
+    pub(crate) fn advance(&mut self, skipped: i32, returned: usize, end_cursor: Vec<u8>) {
+        self.offset = (self.offset - skipped).max(0);
+        self.limit = self.limit.map(|limit| (limit - returned as i32).max(0));
+        self.start_cursor = end_cursor;
+    }
+}
@@ -0,0 +1,367 @@
+use std::borrow::Borrow;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use futures::Stream;
+
+use crate::datastore::api;
+use crate::datastore::api::transaction_options::{Mode, ReadOnly, ReadWrite};
+use crate::datastore::client::convert_timestamp;
+use crate::datastore::{
+    AggregationQuery, AggregationResult, Client, Entity, Error, FromValue, GqlQuery, IntoEntity,
+    Key, LookupResults, Mutation, MutationResult, Query, QueryResults, ReadOptions, Value,
+};
+
+/// The configuration of a Datastore transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionConfig {
+    pub(crate) max_attempts: u32,
+    pub(crate) read_only: bool,
+    pub(crate) read_time: Option<NaiveDateTime>,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> TransactionConfig {
+        TransactionConfig {
+            max_attempts: 5,
+            read_only: false,
+            read_time: None,
+        }
+    }
+}
+
+impl TransactionConfig {
+    /// Sets how many times a transaction is attempted before giving up on contention (defaults to 5).
+    pub fn max_attempts(mut self, value: u32) -> TransactionConfig {
+        self.max_attempts = value.max(1);
+        self
+    }
+
+    /// Only allows reads within the transaction.
+    pub fn read_only(mut self, value: bool) -> TransactionConfig {
+        self.read_only = value;
+        self
+    }
+
+    /// Reads entities as they were at the given time (implies a read-only transaction).
+    pub fn read_time(mut self, value: NaiveDateTime) -> TransactionConfig {
+        self.read_only = true;
+        self.read_time = Some(value);
+        self
+    }
+}
+
+/// A running Datastore transaction.
+///
+/// Reads go through the transaction, while writes are buffered and only applied on commit.
+#[derive(Clone)]
+pub struct Transaction {
+    pub(crate) client: Client,
+    pub(crate) id: Vec<u8>,
+    pub(crate) read_only: bool,
+    pub(crate) mutations: Arc<Mutex<Vec<Mutation>>>,
+}
+
+impl Transaction {
+    /// The identifier of this transaction.
+    pub fn id(&self) -> &[u8] {
+        self.id.as_slice()
+    }
+
+    /// Is this a read-only transaction ?
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn read_options(&self) -> api::ReadOptions {
+        ReadOptions::Transaction(self.id.clone()).into_api()
+    }
+
+    /// Runs a query within the transaction, and lazily streams its results.
+    pub fn query_stream(&self, query: Query) -> impl Stream<Item = Result<Entity, Error>> {
+        self.client.stream_query(query, Some(self.read_options()))
+    }
+
+    /// Runs a GQL query, within the transaction.
+    pub async fn gql_query(&self, query: GqlQuery) -> Result<QueryResults, Error> {
+        let read_options = self.read_options();
+        self.client
+            .clone()
+            .run_gql_query(query, Some(read_options))
+            .await
+    }
+
+    /// Runs an aggregation query, within the transaction.
+    pub async fn aggregate(
+        &self,
+        query: impl Into<AggregationQuery>,
+    ) -> Result<AggregationResult, Error> {
+        let read_options = self.read_options();
+        self.client
+            .clone()
+            .run_aggregation(query.into(), Some(read_options))
+            .await
+    }
+
+    /// Buffers a mutation, to be applied on commit.
+    #[allow(clippy::result_large_err)]
+    pub fn mutate(&self, mutation: Mutation) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::InvalidArgument(String::from(
+                "cannot write within a read-only transaction",
+            )));
+        }
+        self.mutations.lock().unwrap().push(mutation);
+        Ok(())
+    }
+
+    /// Gets an entity from a key, within the transaction.
+    pub async fn get<T, K>(&self, key: K) -> Result<Option<T>, Error>
+    where
+        K: Borrow<Key>,
+        T: FromValue,
+    {
+        let results = self.get_all::<Value, _, _>(Some(key.borrow())).await?;
+        let value = results.found.into_iter().next();
+        Ok(value.map(|(_, value)| T::from_value(value)).transpose()?)
+    }
+
+    /// Gets multiple entities from multiple keys, within the transaction.
+    pub async fn get_all<T, K, I>(&self, keys: I) -> Result<LookupResults<T>, Error>
+    where
+        I: IntoIterator<Item = K>,
+        K: Borrow<Key>,
+        T: FromValue,
+    {
+        let read_options = self.read_options();
+        self.client.clone().lookup(keys, Some(read_options)).await
+    }
+
+    /// Runs a query, within the transaction.
+    pub async fn query(&self, query: Query) -> Result<QueryResults, Error> {
+        let read_options = self.read_options();
+        self.client
+            .clone()
+            .run_query(query, Some(read_options))
+            .await
+    }
+
+    /// Buffers the insertion of a new entity (fails on commit if it already exists).
+    #[allow(clippy::result_large_err)]
+    pub fn insert(&self, entity: impl IntoEntity) -> Result<(), Error> {
+        self.mutate(Mutation::insert(entity)?)
+    }
+
+    /// Buffers the update of an existing entity (fails on commit if it does not exist).
+    #[allow(clippy::result_large_err)]
+    pub fn update(&self, entity: impl IntoEntity) -> Result<(), Error> {
+        self.mutate(Mutation::update(entity)?)
+    }
+
+    /// Buffers the insertion or replacement of an entity.
+    #[allow(clippy::result_large_err)]
+    pub fn upsert(&self, entity: impl IntoEntity) -> Result<(), Error> {
+        self.mutate(Mutation::upsert(entity)?)
+    }
+
+    /// Buffers the write of an entity, like `Client::put` (inserts if its key is incomplete).
+    #[allow(clippy::result_large_err)]
+    pub fn put(&self, entity: impl IntoEntity) -> Result<(), Error> {
+        self.mutate(Mutation::put(entity)?)
+    }
+
+    /// Buffers the deletion of the entity identified by a key.
+    #[allow(clippy::result_large_err)]
+    pub fn delete(&self, key: impl Borrow<Key>) -> Result<(), Error> {
+        self.mutate(Mutation::delete(key))
+    }
+
+    /// Commits the transaction, applying the buffered mutations.
+    ///
+    /// Returns the results of the mutations, in the order they were buffered.
+    pub async fn commit(mut self) -> Result<Vec<MutationResult>, Error> {
+        let mutations = std::mem::take(&mut *self.mutations.lock().unwrap());
+        let response = self
+            .client
+            .commit_mutations(mutations, Some(self.id))
+            .await?;
+        let results = response
+            .mutation_results
+            .into_iter()
+            .map(MutationResult::from)
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Rolls back the transaction, discarding the buffered mutations.
+    pub async fn rollback(mut self) -> Result<(), Error> {
+        self.mutations.lock().unwrap().clear();
+        let request = api::RollbackRequest {
+            project_id: self.client.project_name.clone(),
+            transaction: self.id,
+        };
+        let request = self.client.construct_request(request).await?;
+        self.client.service.rollback(request).await?;
+
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Begins a new read-write transaction.
+    pub async fn begin_transaction(&mut self) -> Result<Transaction, Error> {
+        self.begin_transaction_with_config(TransactionConfig::default())
+            .await
+    }
+
+    /// Begins a new transaction with the given configuration.
+    pub async fn begin_transaction_with_config(
+        &mut self,
+        config: TransactionConfig,
+    ) -> Result<Transaction, Error> {
+        self.begin(&config, None).await
+    }
+
+    async fn begin(
+        &mut self,
+        config: &TransactionConfig,
+        previous: Option<Vec<u8>>,
+    ) -> Result<Transaction, Error> {
+        let mode = if config.read_only {
+            Mode::ReadOnly(ReadOnly {
+                read_time: config.read_time.map(convert_timestamp),
+            })
+        } else {
+            Mode::ReadWrite(ReadWrite {
+                previous_transaction: previous.unwrap_or_default(),
+            })
+        };
+        let request = api::BeginTransactionRequest {
+            project_id: self.project_name.clone(),
+            transaction_options: Some(api::TransactionOptions { mode: Some(mode) }),
+        };
+        let request = self.construct_request(request).await?;
+        let response = self.service.begin_transaction(request).await?;
+        let response = response.into_inner();
+
+        Ok(Transaction {
+            client: self.clone(),
+            id: response.transaction,
+            read_only: config.read_only,
+            mutations: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// Runs a function within a read-write transaction, then commits it.
+    ///
+    /// If the function fails, the transaction is rolled back and the error is returned.
+    /// If the function or the commit is aborted because of contention, the whole function
+    /// is retried in a new transaction (with exponential backoff).
+    pub async fn transaction<F, Fut, T>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnMut(Transaction) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        self.transaction_with_config(TransactionConfig::default(), f)
+            .await
+    }
+
+    /// Runs a function within a transaction with the given configuration, then commits it.
+    pub async fn transaction_with_config<F, Fut, T>(
+        &mut self,
+        config: TransactionConfig,
+        f: F,
+    ) -> Result<T, Error>
+    where
+        F: FnMut(Transaction) -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        run_transaction(self, config, f).await
+    }
+}
+
+/// The calls transactions are made of, as used by `Client::transaction_with_config`.
+///
+/// This lets the retry logic run against something else than a store.
+pub(crate) trait TransactionRunner {
+    /// Begins a transaction, retrying the previous (aborted) one if any.
+    async fn begin(
+        &mut self,
+        config: &TransactionConfig,
+        previous: Option<Vec<u8>>,
+    ) -> Result<Transaction, Error>;
+
+    /// Commits a transaction.
+    async fn commit(&mut self, transaction: Transaction) -> Result<(), Error>;
+
+    /// Rolls back a transaction.
+    async fn rollback(&mut self, transaction: Transaction) -> Result<(), Error>;
+}
+
+impl TransactionRunner for Client {
+    async fn begin(
+        &mut self,
+        config: &TransactionConfig,
+        previous: Option<Vec<u8>>,
+    ) -> Result<Transaction, Error> {
+        Client::begin(self, config, previous).await
+    }
+
+    async fn commit(&mut self, transaction: Transaction) -> Result<(), Error> {
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(&mut self, transaction: Transaction) -> Result<(), Error> {
+        transaction.rollback().await
+    }
+}
+
+/// Runs a function within transactions, until one is committed or fails without contention.
+pub(crate) async fn run_transaction<R, F, Fut, T>(
+    runner: &mut R,
+    config: TransactionConfig,
+    mut f: F,
+) -> Result<T, Error>
+where
+    R: TransactionRunner,
+    F: FnMut(Transaction) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut previous = None;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let transaction = runner.begin(&config, previous.take()).await?;
+        let id = transaction.id.clone();
+        let result = match f(transaction.clone()).await {
+            Ok(output) => runner.commit(transaction).await.map(|_| output),
+            Err(err) => {
+                // The original error matters more than a failed rollback.
+                let _ = runner.rollback(transaction).await;
+                Err(err)
+            }
+        };
+        match result {
+            Ok(output) => return Ok(output),
+            Err(err) if is_retryable(&err) && attempt < config.max_attempts => {
+                tokio::time::sleep(retry_backoff(attempt)).await;
+                previous = Some(id);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Whether a transaction that failed with this error should be retried (on contention).
+pub(crate) fn is_retryable(err: &Error) -> bool {
+    matches!(err, Error::Status(status) if status.code() == tonic::Code::Aborted)
+}
+
+/// How long to wait before the next attempt of a transaction (exponential, capped at 6.4 seconds).
+pub(crate) fn retry_backoff(attempt: u32) -> Duration {
+    Duration::from_millis(100 << attempt.min(6))
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::iter::FromIterator;
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime};
 
 #[cfg(feature = "bytes")]
 use bytes::Bytes;
@@ -322,7 +322,8 @@ impl From<ValueType> for Value {
             ValueType::IntegerValue(val) => Value::IntegerValue(val),
             ValueType::DoubleValue(val) => Value::DoubleValue(val),
             ValueType::TimestampValue(val) => {
-                Value::TimestampValue(NaiveDateTime::from_timestamp(val.seconds, val.nanos as u32))
+                let time = DateTime::from_timestamp(val.seconds, val.nanos as u32);
+                Value::TimestampValue(time.expect("timestamp out of range").naive_utc())
             }
             ValueType::KeyValue(key) => Value::KeyValue(Key::from(key)),
             ValueType::StringValue(val) => Value::StringValue(val),
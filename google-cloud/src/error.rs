@@ -25,6 +25,30 @@ pub enum Error {
     #[cfg(feature = "storage")]
     #[error("HTTP error: {0}")]
     Reqwest(#[from] reqwest::Error),
+    /// A request precondition was not met (HTTP 412 or 304 status).
+    #[cfg(feature = "storage")]
+    #[error("precondition failed")]
+    PreconditionFailed,
+    /// Downloaded data does not match the checksum reported by Cloud Storage.
+    #[cfg(feature = "storage")]
+    #[error("{algorithm} checksum mismatch: expected `{expected}`, computed `{computed}`")]
+    ChecksumMismatch {
+        /// The checksum algorithm (`crc32c` or `md5`).
+        algorithm: String,
+        /// The checksum reported by Cloud Storage.
+        expected: String,
+        /// The checksum computed from the received data.
+        computed: String,
+    },
+    /// A sub-request of a batch received an unsuccessful status.
+    #[cfg(feature = "storage")]
+    #[error("batch sub-request failed with status {status}: {message}")]
+    BatchItem {
+        /// The HTTP status code of the sub-response.
+        status: u16,
+        /// The error message of the sub-response.
+        message: String,
+    },
     /// conversion error (`try_from(..)` or `try_into(..)` errors).
     #[error("conversion error: {0}")]
     Convert(#[from] ConvertError),
@@ -34,6 +58,12 @@ pub enum Error {
     #[error("invalid uri error: {0}")]
     /// conversion error from uri bytes
     InvalidBytes(#[from] http::uri::InvalidUri),
+    /// An unexpected or malformed response was received.
+    #[error("unexpected response: {0}")]
+    Response(String),
+    /// An invalid argument was passed.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
 }
 
 /// The error type for value conversions.
@@ -67,4 +97,10 @@ pub enum AuthError {
     /// Hyper errors
     #[error("Hyper error: {0}")]
     Hyper(#[from] hyper::Error),
+    /// No credentials are available for the requested operation.
+    #[error("missing credentials")]
+    MissingCredentials,
+    /// An invalid private key, or a failure while signing with it.
+    #[error("signing error: {0}")]
+    Signing(String),
 }
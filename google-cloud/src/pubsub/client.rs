@@ -45,7 +45,7 @@ impl Client {
     /// Credentials are looked up in the `GOOGLE_APPLICATION_CREDENTIALS` environment variable.
     pub async fn new(project_name: impl Into<String>) -> Result<Client, Error> {
         if let Ok(host) = env::var("PUBSUB_EMULATOR_HOST") {
-            return Client::insecure(project_name, host).await
+            return Client::insecure(project_name, host).await;
         }
 
         let path = env::var("GOOGLE_APPLICATION_CREDENTIALS")?;
@@ -78,7 +78,7 @@ impl Client {
 
     /// Create a new client for the specified project with custom credentials.
     /// Allows creation of client without credentials, should not be called directly
-    async fn from_credentials(
+    pub(crate) async fn from_credentials(
         project_name: impl Into<String>,
         creds: ApplicationCredentials,
     ) -> Result<Client, Error> {
@@ -2,6 +2,7 @@ mod client;
 mod message;
 mod subscription;
 mod topic;
+#[allow(clippy::all)]
 mod api {
     include!("api/google.pubsub.v1.rs");
 }
@@ -105,10 +105,12 @@ impl Subscription {
                     message_id: message.message_id,
                     ack_id: handle.ack_id,
                     attributes: message.attributes,
-                    publish_time: chrono::NaiveDateTime::from_timestamp(
+                    publish_time: chrono::DateTime::from_timestamp(
                         timestamp.seconds,
                         timestamp.nanos as u32,
-                    ),
+                    )
+                    .expect("timestamp out of range")
+                    .naive_utc(),
                 };
                 break Some(message);
             } else {
@@ -4,7 +4,7 @@ use crate::pubsub::api;
 use crate::pubsub::{Client, Error, Subscription, SubscriptionConfig};
 
 /// Represents the topic's configuration.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct TopicConfig {
     pub(crate) labels: HashMap<String, String>,
 }
@@ -17,14 +17,6 @@ impl TopicConfig {
     }
 }
 
-impl Default for TopicConfig {
-    fn default() -> TopicConfig {
-        TopicConfig {
-            labels: HashMap::new(),
-        }
-    }
-}
-
 /// Represents a topic.
 #[derive(Clone)]
 pub struct Topic {
@@ -62,7 +54,7 @@ impl Topic {
             retain_acked_messages: config.message_retention_duration.is_some(),
             message_retention_duration: config.message_retention_duration.map(|mut dur| {
                 let seconds = dur.num_seconds();
-                dur = dur - chrono::Duration::seconds(seconds);
+                dur -= chrono::Duration::seconds(seconds);
                 let nanos = dur.num_nanoseconds().unwrap_or(0) as i32;
                 prost_types::Duration { seconds, nanos }
             }),
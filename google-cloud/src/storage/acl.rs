@@ -0,0 +1,441 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use json::json;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::api::bucket_acl::{BucketAclResource, BucketAclResources};
+use crate::storage::api::object_acl::{ObjectAclResource, ObjectAclResources};
+use crate::storage::{Bucket, Client, Error, Object};
+
+/// Represents the role granted by an access control entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum AclRole {
+    /// Can read the resource (and its metadata).
+    Reader,
+    /// Can read and write the resource (only applicable to buckets).
+    Writer,
+    /// Full control over the resource, including its access control.
+    Owner,
+}
+
+/// Represents a team of a project, as used in project access control entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProjectTeam {
+    /// The owners of the project.
+    Owners,
+    /// The editors of the project.
+    Editors,
+    /// The viewers of the project.
+    Viewers,
+}
+
+impl ProjectTeam {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProjectTeam::Owners => "owners",
+            ProjectTeam::Editors => "editors",
+            ProjectTeam::Viewers => "viewers",
+        }
+    }
+}
+
+/// Represents the entity an access control entry applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AclEntity {
+    /// A user, identified by its email address or its ID.
+    User(String),
+    /// A Google group, identified by its email address or its ID.
+    Group(String),
+    /// All users of a Google Workspace domain.
+    Domain(String),
+    /// A team of a project, identified by its project number (or ID).
+    Project(ProjectTeam, String),
+    /// Anyone on the internet.
+    AllUsers,
+    /// Anyone signed in with a Google account.
+    AllAuthenticatedUsers,
+}
+
+impl fmt::Display for AclEntity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AclEntity::User(id) => write!(f, "user-{}", id),
+            AclEntity::Group(id) => write!(f, "group-{}", id),
+            AclEntity::Domain(domain) => write!(f, "domain-{}", domain),
+            AclEntity::Project(team, id) => write!(f, "project-{}-{}", team.as_str(), id),
+            AclEntity::AllUsers => write!(f, "allUsers"),
+            AclEntity::AllAuthenticatedUsers => write!(f, "allAuthenticatedUsers"),
+        }
+    }
+}
+
+impl TryFrom<String> for AclEntity {
+    type Error = Error;
+
+    fn try_from(entity: String) -> Result<AclEntity, Error> {
+        let (kind, rest) = match entity.as_str() {
+            "allUsers" => return Ok(AclEntity::AllUsers),
+            "allAuthenticatedUsers" => return Ok(AclEntity::AllAuthenticatedUsers),
+            _ => entity.split_at(entity.find('-').unwrap_or(0)),
+        };
+        let rest = rest.trim_start_matches('-');
+        let team = |team: &str| match team {
+            "owners" => Some(ProjectTeam::Owners),
+            "editors" => Some(ProjectTeam::Editors),
+            "viewers" => Some(ProjectTeam::Viewers),
+            _ => None,
+        };
+        let parsed = match kind {
+            "user" => Some(AclEntity::User(String::from(rest))),
+            "group" => Some(AclEntity::Group(String::from(rest))),
+            "domain" => Some(AclEntity::Domain(String::from(rest))),
+            "project" => rest.find('-').and_then(|idx| {
+                let (name, project) = rest.split_at(idx);
+                team(name).map(|team| AclEntity::Project(team, String::from(&project[1..])))
+            }),
+            _ => None,
+        };
+
+        parsed.ok_or_else(|| Error::InvalidArgument(format!("unknown ACL entity: {}", entity)))
+    }
+}
+
+impl From<AclEntity> for String {
+    fn from(entity: AclEntity) -> String {
+        entity.to_string()
+    }
+}
+
+/// Represents an access control entry of a bucket or an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AclEntry {
+    pub(crate) entity: AclEntity,
+    pub(crate) role: AclRole,
+    pub(crate) email: Option<String>,
+    pub(crate) domain: Option<String>,
+}
+
+impl AclEntry {
+    /// Get the entity this entry applies to.
+    pub fn entity(&self) -> &AclEntity {
+        &self.entity
+    }
+
+    /// Get the role granted to the entity.
+    pub fn role(&self) -> AclRole {
+        self.role
+    }
+
+    /// Get the email address associated with the entity, if any.
+    pub fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    /// Get the domain associated with the entity, if any.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+}
+
+impl From<BucketAclResource> for AclEntry {
+    fn from(resource: BucketAclResource) -> AclEntry {
+        AclEntry {
+            entity: resource.entity,
+            role: resource.role,
+            email: resource.email,
+            domain: resource.domain,
+        }
+    }
+}
+
+impl From<ObjectAclResource> for AclEntry {
+    fn from(resource: ObjectAclResource) -> AclEntry {
+        AclEntry {
+            entity: resource.entity,
+            role: resource.role,
+            email: resource.email,
+            domain: resource.domain,
+        }
+    }
+}
+
+impl From<BucketAclResources> for Vec<AclEntry> {
+    fn from(resources: BucketAclResources) -> Vec<AclEntry> {
+        resources.items.into_iter().map(AclEntry::from).collect()
+    }
+}
+
+impl From<ObjectAclResources> for Vec<AclEntry> {
+    fn from(resources: ObjectAclResources) -> Vec<AclEntry> {
+        resources.items.into_iter().map(AclEntry::from).collect()
+    }
+}
+
+/// The access control list of a resource, designated by its URI.
+struct AclList<'a> {
+    client: &'a mut Client,
+    uri: String,
+    query: Vec<(&'static str, String)>,
+}
+
+impl<'a> AclList<'a> {
+    fn entry_uri(&self, entity: &AclEntity) -> String {
+        format!(
+            "{}/{}",
+            self.uri,
+            utf8_percent_encode(&entity.to_string(), NON_ALPHANUMERIC),
+        )
+    }
+
+    async fn list<R: DeserializeOwned + Into<Vec<AclEntry>>>(self) -> Result<Vec<AclEntry>, Error> {
+        let inner = &self.client.client;
+
+        let token = self.client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(self.uri.as_str())
+            .query(&self.query)
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resources = Client::check_status(response)?.json::<R>().await?;
+
+        Ok(resources.into())
+    }
+
+    async fn get<R: DeserializeOwned + Into<AclEntry>>(
+        self,
+        entity: &AclEntity,
+    ) -> Result<AclEntry, Error> {
+        let inner = &self.client.client;
+        let uri = self.entry_uri(entity);
+
+        let token = self.client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .query(&self.query)
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json::<R>().await?;
+
+        Ok(resource.into())
+    }
+
+    async fn insert<R: DeserializeOwned + Into<AclEntry>>(
+        self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        let inner = &self.client.client;
+
+        let body = json!({
+            "entity": entity,
+            "role": role,
+        });
+        let token = self.client.token_manager.lock().await.token().await?;
+        let request = inner
+            .post(self.uri.as_str())
+            .query(&self.query)
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json::<R>().await?;
+
+        Ok(resource.into())
+    }
+
+    async fn update<R: DeserializeOwned + Into<AclEntry>>(
+        self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        let inner = &self.client.client;
+        let uri = self.entry_uri(entity);
+
+        let body = json!({
+            "entity": entity,
+            "role": role,
+        });
+        let token = self.client.token_manager.lock().await.token().await?;
+        let request = inner
+            .put(uri.as_str())
+            .query(&self.query)
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json::<R>().await?;
+
+        Ok(resource.into())
+    }
+
+    async fn delete(self, entity: &AclEntity) -> Result<(), Error> {
+        let inner = &self.client.client;
+        let uri = self.entry_uri(entity);
+
+        let token = self.client.token_manager.lock().await.token().await?;
+        let request = inner
+            .delete(uri.as_str())
+            .query(&self.query)
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        Client::check_status(response)?;
+
+        Ok(())
+    }
+}
+
+impl Bucket {
+    fn acl_list(&mut self, list: &str) -> AclList<'_> {
+        let uri = format!(
+            "{}/b/{}/{}",
+            self.client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+            list,
+        );
+        AclList {
+            client: &mut self.client,
+            uri,
+            query: Vec::new(),
+        }
+    }
+
+    /// List the access control entries of the bucket.
+    pub async fn acl(&mut self) -> Result<Vec<AclEntry>, Error> {
+        self.acl_list("acl").list::<BucketAclResources>().await
+    }
+
+    /// Get the bucket's access control entry for the given entity.
+    pub async fn acl_entry(&mut self, entity: &AclEntity) -> Result<AclEntry, Error> {
+        self.acl_list("acl").get::<BucketAclResource>(entity).await
+    }
+
+    /// Grant a role on the bucket to the given entity.
+    pub async fn insert_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list("acl")
+            .insert::<BucketAclResource>(entity, role)
+            .await
+    }
+
+    /// Change the role on the bucket granted to the given entity.
+    pub async fn update_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list("acl")
+            .update::<BucketAclResource>(entity, role)
+            .await
+    }
+
+    /// Revoke the bucket's access control entry for the given entity.
+    pub async fn delete_acl(&mut self, entity: &AclEntity) -> Result<(), Error> {
+        self.acl_list("acl").delete(entity).await
+    }
+
+    /// List the default access control entries applied to new objects of the bucket.
+    pub async fn default_object_acl(&mut self) -> Result<Vec<AclEntry>, Error> {
+        self.acl_list("defaultObjectAcl")
+            .list::<ObjectAclResources>()
+            .await
+    }
+
+    /// Get the default object access control entry for the given entity.
+    pub async fn default_object_acl_entry(
+        &mut self,
+        entity: &AclEntity,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list("defaultObjectAcl")
+            .get::<ObjectAclResource>(entity)
+            .await
+    }
+
+    /// Grant a role, by default, on new objects of the bucket to the given entity.
+    pub async fn insert_default_object_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list("defaultObjectAcl")
+            .insert::<ObjectAclResource>(entity, role)
+            .await
+    }
+
+    /// Change the role granted, by default, on new objects of the bucket to the given entity.
+    pub async fn update_default_object_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list("defaultObjectAcl")
+            .update::<ObjectAclResource>(entity, role)
+            .await
+    }
+
+    /// Revoke the default object access control entry for the given entity.
+    pub async fn delete_default_object_acl(&mut self, entity: &AclEntity) -> Result<(), Error> {
+        self.acl_list("defaultObjectAcl").delete(entity).await
+    }
+}
+
+impl Object {
+    fn acl_list(&mut self) -> AclList<'_> {
+        let uri = format!("{}/acl", self.uri());
+        let query = self.query(&Default::default());
+        AclList {
+            client: &mut self.client,
+            uri,
+            query,
+        }
+    }
+
+    /// List the access control entries of the object.
+    ///
+    /// If the handle is pinned to a generation, the entries of that generation are listed.
+    pub async fn acl(&mut self) -> Result<Vec<AclEntry>, Error> {
+        self.acl_list().list::<ObjectAclResources>().await
+    }
+
+    /// Get the object's access control entry for the given entity.
+    pub async fn acl_entry(&mut self, entity: &AclEntity) -> Result<AclEntry, Error> {
+        self.acl_list().get::<ObjectAclResource>(entity).await
+    }
+
+    /// Grant a role on the object to the given entity.
+    pub async fn insert_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list()
+            .insert::<ObjectAclResource>(entity, role)
+            .await
+    }
+
+    /// Change the role on the object granted to the given entity.
+    pub async fn update_acl(
+        &mut self,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> Result<AclEntry, Error> {
+        self.acl_list()
+            .update::<ObjectAclResource>(entity, role)
+            .await
+    }
+
+    /// Revoke the object's access control entry for the given entity.
+    pub async fn delete_acl(&mut self, entity: &AclEntity) -> Result<(), Error> {
+        self.acl_list().delete(entity).await
+    }
+}
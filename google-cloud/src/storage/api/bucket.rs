@@ -46,24 +46,63 @@ pub struct BucketResource {
     pub etag: String,
 }
 
+/// The modifiable fields of a bucket, used to create or patch it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketPatchResource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub versioning: Option<BucketVersioning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<BucketLifecycle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention_policy: Option<BucketRetentionPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors: Option<Vec<BucketCors>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<BucketWebsite>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<BucketLogging>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<BucketEncryption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billing: Option<BucketBilling>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iam_configuration: Option<BucketIamConfig>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketRetentionPolicy {
-    pub pubretention_period: u64,
-    pub pubeffective_time: String,
-    pub pubis_locked: bool,
+    /// The retention period, in seconds (an int64 formatted as a string).
+    pub retention_period: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_locked: Option<bool>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketIamConfig {
-    pub uniform_bucket_level_access: BucketUniformLevelAccess,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uniform_bucket_level_access: Option<BucketUniformLevelAccess>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_access_prevention: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketUniformLevelAccess {
     pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub locked_time: Option<String>,
 }
 
@@ -77,21 +116,24 @@ pub struct BucketEncryption {
 #[serde(rename_all = "camelCase")]
 pub struct BucketOwner {
     pub entity: String,
-    pub entity_id: String,
+    pub entity_id: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketWebsite {
-    pub main_page_suffix: String,
-    pub not_found_page: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main_page_suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_found_page: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketLogging {
     pub log_bucket: String,
-    pub log_object_prefix: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_object_prefix: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -100,18 +142,23 @@ pub struct BucketVersioning {
     pub enabled: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketCors {
+    #[serde(default)]
     pub origin: Vec<String>,
+    #[serde(default)]
     pub method: Vec<String>,
+    #[serde(default)]
     pub response_header: Vec<String>,
-    pub max_age_seconds: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_seconds: Option<i32>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketLifecycle {
+    #[serde(default)]
     pub rule: Vec<BucketRule>,
 }
 
@@ -127,17 +174,35 @@ pub struct BucketRule {
 pub struct BucketRuleAction {
     #[serde(rename = "type")]
     pub action_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_class: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketRuleCondition {
-    pub age: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub created_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_live: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub matches_storage_class: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub num_newer_versions: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_time_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_since_custom_time: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days_since_noncurrent_time: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noncurrent_time_before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_prefix: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_suffix: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
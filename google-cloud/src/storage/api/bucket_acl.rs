@@ -1,5 +1,16 @@
 use serde::{Deserialize, Serialize};
 
+use crate::storage::{AclEntity, AclRole};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketAclResources {
+    /// Value: "storage#bucketAccessControls"
+    pub kind: String,
+    #[serde(default)]
+    pub items: Vec<BucketAclResource>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BucketAclResource {
@@ -8,12 +19,12 @@ pub struct BucketAclResource {
     pub id: String,
     pub self_link: String,
     pub bucket: String,
-    pub entity: String,
-    pub role: String,
-    pub email: String,
-    pub entity_id: String,
-    pub domain: String,
-    pub project_team: BucketAclProjectTeam,
+    pub entity: AclEntity,
+    pub role: AclRole,
+    pub email: Option<String>,
+    pub entity_id: Option<String>,
+    pub domain: Option<String>,
+    pub project_team: Option<BucketAclProjectTeam>,
     pub etag: String,
 }
 
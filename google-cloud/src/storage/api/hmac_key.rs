@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::storage::HmacKeyState;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacKeyResource {
+    /// Value: "storage#hmacKey"
+    pub kind: String,
+    pub metadata: HmacKeyMetadataResource,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacKeysMetadataResources {
+    /// Value: "storage#hmacKeysMetadata"
+    pub kind: String,
+    #[serde(default)]
+    pub items: Vec<HmacKeyMetadataResource>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacKeyMetadataResource {
+    /// Value: "storage#hmacKeyMetadata"
+    pub kind: String,
+    pub id: String,
+    pub self_link: Option<String>,
+    pub access_id: String,
+    pub project_id: String,
+    pub service_account_email: String,
+    pub state: HmacKeyState,
+    pub time_created: String,
+    pub updated: String,
+    pub etag: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HmacKeyUpdateResource {
+    pub state: HmacKeyState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceAccountResource {
+    /// Value: "storage#serviceAccount"
+    pub kind: String,
+    #[serde(rename = "email_address")]
+    pub email_address: String,
+}
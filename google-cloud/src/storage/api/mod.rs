@@ -1,4 +1,11 @@
 pub mod bucket;
 pub mod bucket_acl;
+pub mod hmac_key;
+pub mod notification;
 pub mod object;
 pub mod object_acl;
+
+/// Types of the ByteStream API, used by the gRPC transport.
+pub mod bytestream {
+    include!("google.bytestream.rs");
+}
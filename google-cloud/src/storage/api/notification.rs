@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{EventType, PayloadFormat};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationResources {
+    /// Value: "storage#notifications"
+    pub kind: String,
+    #[serde(default)]
+    pub items: Vec<NotificationResource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationResource {
+    /// Value: "storage#notification"
+    pub kind: String,
+    pub id: String,
+    pub self_link: String,
+    pub topic: String,
+    pub event_types: Option<Vec<EventType>>,
+    pub custom_attributes: Option<HashMap<String, String>>,
+    pub payload_format: PayloadFormat,
+    pub object_name_prefix: Option<String>,
+    pub etag: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationInsertResource {
+    pub topic: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_types: Option<Vec<EventType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_attributes: Option<HashMap<String, String>>,
+    pub payload_format: PayloadFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_name_prefix: Option<String>,
+}
@@ -3,6 +3,19 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 use crate::storage::api::object_acl::ObjectAclResource;
+use crate::storage::RetentionMode;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectResources {
+    /// Value: "storage#objects"
+    pub kind: String,
+    #[serde(default)]
+    pub items: Vec<ObjectResource>,
+    #[serde(default)]
+    pub prefixes: Vec<String>,
+    pub next_page_token: Option<String>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +28,7 @@ pub struct ObjectResource {
     pub bucket: String,
     pub generation: String,
     pub metageneration: String,
+    #[serde(default)]
     pub content_type: String,
     pub time_created: String,
     pub updated: String,
@@ -22,10 +36,11 @@ pub struct ObjectResource {
     pub temporary_hold: Option<bool>,
     pub event_based_hold: Option<bool>,
     pub retention_expiration_time: Option<String>,
+    pub retention: Option<ObjectRetentionResource>,
     pub storage_class: String,
     pub time_storage_class_updated: Option<String>,
     pub size: String,
-    pub md5_hash: String,
+    pub md5_hash: Option<String>,
     pub media_link: String,
     pub content_encoding: Option<String>,
     pub content_disposition: Option<String>,
@@ -48,9 +63,92 @@ pub struct ObjectOwner {
     pub entity_id: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectRetentionResource {
+    pub mode: RetentionMode,
+    pub retain_until_time: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectCustomerEncryption {
     pub encryption_algorithm: String,
     pub key_sha256: String,
 }
+
+/// The metadata sent along with the data of an uploaded object.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectInsertResource {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crc32c: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub md5_hash: Option<String>,
+    #[serde(flatten)]
+    pub config: ObjectPatchResource,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectPatchResource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_disposition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temporary_hold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_based_hold: Option<bool>,
+    /// `Some(None)` removes the object's retention.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<Option<ObjectRetentionResource>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewriteResponse {
+    /// Value: "storage#rewriteResponse"
+    pub kind: String,
+    pub total_bytes_rewritten: String,
+    pub object_size: String,
+    pub done: bool,
+    pub rewrite_token: Option<String>,
+    pub resource: Option<ObjectResource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeRequest {
+    /// Value: "storage#composeRequest"
+    pub kind: String,
+    pub source_objects: Vec<ComposeSourceObject>,
+    pub destination: ObjectPatchResource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeSourceObject {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_preconditions: Option<ComposeSourcePreconditions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposeSourcePreconditions {
+    pub if_generation_match: String,
+}
@@ -1,16 +1,32 @@
 use serde::{Deserialize, Serialize};
 
+use crate::storage::{AclEntity, AclRole};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectAclResources {
+    /// Value: "storage#objectAccessControls"
+    pub kind: String,
+    #[serde(default)]
+    pub items: Vec<ObjectAclResource>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectAclResource {
     /// Value: "storage#objectAccessControl"
     pub kind: String,
-    pub entity: String,
-    pub role: String,
-    pub email: String,
-    pub entity_id: String,
-    pub domain: String,
-    pub project_team: ObjectAclProjectTeam,
+    pub id: Option<String>,
+    pub self_link: Option<String>,
+    pub bucket: Option<String>,
+    pub object: Option<String>,
+    pub generation: Option<String>,
+    pub entity: AclEntity,
+    pub role: AclRole,
+    pub email: Option<String>,
+    pub entity_id: Option<String>,
+    pub domain: Option<String>,
+    pub project_team: Option<ObjectAclProjectTeam>,
     pub etag: String,
 }
 
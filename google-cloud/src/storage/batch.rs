@@ -0,0 +1,362 @@
+use std::fmt::Write;
+
+use json::json;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+use crate::storage::api::object::{ObjectPatchResource, ObjectResource};
+use crate::storage::api::object_acl::ObjectAclResource;
+use crate::storage::{
+    random_token, AclEntity, AclEntry, AclRole, Client, Conditions, Error, Object, ObjectConfig,
+};
+
+/// The batch endpoint accepts at most 100 sub-requests per request.
+pub(crate) const MAX_BATCH_SIZE: usize = 100;
+
+/// The kind of response expected from a sub-request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Empty,
+    Object,
+    AclEntry,
+}
+
+/// A sub-request of a batch.
+#[derive(Debug, Clone)]
+pub(crate) struct BatchRequest {
+    method: &'static str,
+    path: String,
+    query: Vec<(&'static str, String)>,
+    body: Option<Vec<u8>>,
+    expect: Expect,
+}
+
+/// A sub-response of a batch, as parsed from the `multipart/mixed` response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BatchPart {
+    /// The (zero-based) index of the matching sub-request, if the part had a `Content-ID` header.
+    pub(crate) index: Option<usize>,
+    pub(crate) status: u16,
+    pub(crate) body: String,
+}
+
+/// Represents the successful outcome of a sub-request of a batch.
+#[derive(Clone)]
+pub enum BatchResponse {
+    /// The targeted resource has been deleted.
+    Deleted,
+    /// The targeted object, as updated.
+    Object(Box<Object>),
+    /// The created access control entry.
+    AclEntry(AclEntry),
+}
+
+/// Represents a batch of object deletions, patches and ACL updates, sent at once.
+///
+/// Batches larger than 100 sub-requests are automatically split into multiple requests.
+#[derive(Clone)]
+pub struct Batch {
+    pub(crate) client: Client,
+    pub(crate) requests: Vec<BatchRequest>,
+}
+
+impl Batch {
+    pub(crate) fn new(client: Client) -> Batch {
+        Batch {
+            client,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Get the number of sub-requests in the batch.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Is the batch empty ?
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    fn object_path(bucket: &str, name: &str) -> String {
+        format!(
+            "/b/{}/o/{}",
+            utf8_percent_encode(bucket, NON_ALPHANUMERIC),
+            utf8_percent_encode(name, NON_ALPHANUMERIC),
+        )
+    }
+
+    fn push(
+        &mut self,
+        method: &'static str,
+        path: String,
+        query: Vec<(&'static str, String)>,
+        body: Option<json::Value>,
+        expect: Expect,
+    ) -> &mut Batch {
+        let body = body.map(|body| body.to_string().into_bytes());
+        self.requests.push(BatchRequest {
+            method,
+            path,
+            query,
+            body,
+            expect,
+        });
+        self
+    }
+
+    /// Delete an object.
+    pub fn delete_object(&mut self, bucket: &str, name: &str) -> &mut Batch {
+        self.delete_object_with_conditions(bucket, name, Conditions::default())
+    }
+
+    /// Delete an object, if the given conditions are met.
+    pub fn delete_object_with_conditions(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        conditions: Conditions,
+    ) -> &mut Batch {
+        let path = Batch::object_path(bucket, name);
+        self.push("DELETE", path, conditions.query(), None, Expect::Empty)
+    }
+
+    /// Update the metadata of an object.
+    pub fn patch_object(&mut self, bucket: &str, name: &str, config: ObjectConfig) -> &mut Batch {
+        self.patch_object_with_conditions(bucket, name, config, Conditions::default())
+    }
+
+    /// Update the metadata of an object, if the given conditions are met.
+    pub fn patch_object_with_conditions(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        config: ObjectConfig,
+        conditions: Conditions,
+    ) -> &mut Batch {
+        let path = Batch::object_path(bucket, name);
+        let body = json::to_value(ObjectPatchResource::from(config)).ok();
+        self.push("PATCH", path, conditions.query(), body, Expect::Object)
+    }
+
+    /// Grant a role on an object to the given entity.
+    pub fn insert_object_acl(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        entity: &AclEntity,
+        role: AclRole,
+    ) -> &mut Batch {
+        let path = format!("{}/acl", Batch::object_path(bucket, name));
+        let body = json!({
+            "entity": entity,
+            "role": role,
+        });
+        self.push("POST", path, Vec::new(), Some(body), Expect::AclEntry)
+    }
+
+    /// Revoke the given entity's access to an object.
+    pub fn delete_object_acl(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        entity: &AclEntity,
+    ) -> &mut Batch {
+        let path = format!(
+            "{}/acl/{}",
+            Batch::object_path(bucket, name),
+            utf8_percent_encode(&entity.to_string(), NON_ALPHANUMERIC),
+        );
+        self.push("DELETE", path, Vec::new(), None, Expect::Empty)
+    }
+
+    /// Send the batch, and get the outcome of each sub-request (in the order they were added).
+    ///
+    /// The returned error is only about the batch as a whole: failed sub-requests
+    /// are reported by their own results.
+    #[allow(clippy::result_large_err)]
+    pub async fn send(self) -> Result<Vec<Result<BatchResponse, Error>>, Error> {
+        let client = self.client;
+        let mut results = Vec::with_capacity(self.requests.len());
+        for chunk in self.requests.chunks(MAX_BATCH_SIZE) {
+            let boundary = boundary()?;
+            let body = encode(chunk, &boundary);
+            let inner = &client.client;
+
+            let token = client.token_manager.lock().await.token().await?;
+            let request = inner
+                .post(client.batch_endpoint().as_str())
+                .header("authorization", token)
+                .header(
+                    "content-type",
+                    format!("multipart/mixed; boundary={}", boundary),
+                )
+                .body(body)
+                .send();
+            let response = Client::check_status(request.await?)?;
+            let content_type = response
+                .headers()
+                .get("content-type")
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+                .unwrap_or_default();
+            let boundary = content_type
+                .split(';')
+                .filter_map(|param| param.trim().strip_prefix("boundary="))
+                .map(|boundary| boundary.trim_matches('"'))
+                .next()
+                .ok_or_else(|| {
+                    Error::Response(format!(
+                        "unexpected batch response content type: {}",
+                        content_type
+                    ))
+                })?;
+            let text = response.text().await?;
+            let parts = parse(boundary, &text);
+
+            let mut chunk_results: Vec<Option<Result<BatchResponse, Error>>> =
+                chunk.iter().map(|_| None).collect();
+            for (position, part) in parts.into_iter().enumerate() {
+                let index = part.index.unwrap_or(position);
+                if let Some(request) = chunk.get(index) {
+                    chunk_results[index] = Some(outcome(&client, request.expect, part));
+                }
+            }
+            results.extend(chunk_results.into_iter().map(|result| {
+                result.unwrap_or_else(|| {
+                    Err(Error::Response(String::from(
+                        "missing sub-response in batch response",
+                    )))
+                })
+            }));
+        }
+
+        Ok(results)
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn boundary() -> Result<String, Error> {
+    Ok(format!("batch_{}", random_token(16)?))
+}
+
+/// Encode sub-requests as a `multipart/mixed` body, identifying each by its index.
+pub(crate) fn encode(requests: &[BatchRequest], boundary: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (index, request) in requests.iter().enumerate() {
+        let mut head = format!(
+            "--{}\r\ncontent-type: application/http\r\ncontent-id: <{}>\r\n\r\n{} /storage/v1{}",
+            boundary,
+            index + 1,
+            request.method,
+            request.path,
+        );
+        for (idx, (name, value)) in request.query.iter().enumerate() {
+            let separator = if idx == 0 { '?' } else { '&' };
+            let value = utf8_percent_encode(value, NON_ALPHANUMERIC);
+            let _ = write!(head, "{}{}={}", separator, name, value);
+        }
+        head.push_str(" HTTP/1.1\r\n");
+        match request.body.as_ref() {
+            Some(data) => {
+                head.push_str("content-type: application/json; charset=UTF-8\r\n");
+                let _ = write!(head, "content-length: {}\r\n\r\n", data.len());
+                body.extend_from_slice(head.as_bytes());
+                body.extend_from_slice(data);
+                body.extend_from_slice(b"\r\n");
+            }
+            None => {
+                head.push_str("\r\n");
+                body.extend_from_slice(head.as_bytes());
+            }
+        }
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Split a block of text at its first empty line, into headers and the rest.
+fn split_headers(text: &str) -> (&str, &str) {
+    let crlf = text.find("\r\n\r\n").map(|idx| (idx, 4));
+    let lf = text.find("\n\n").map(|idx| (idx, 2));
+    let split = match (crlf, lf) {
+        (Some(crlf), Some(lf)) => Some(if crlf.0 < lf.0 { crlf } else { lf }),
+        (crlf, lf) => crlf.or(lf),
+    };
+    match split {
+        Some((idx, len)) => (&text[..idx], &text[idx + len..]),
+        None => (text, ""),
+    }
+}
+
+/// Parse the sub-responses of a `multipart/mixed` batch response.
+pub(crate) fn parse(boundary: &str, text: &str) -> Vec<BatchPart> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    for section in text.split(delimiter.as_str()).skip(1) {
+        if section.starts_with("--") {
+            break;
+        }
+        let (headers, http) = split_headers(section.trim_start());
+        let index = headers
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                if !name.trim().eq_ignore_ascii_case("content-id") {
+                    return None;
+                }
+                let value = value.trim().trim_start_matches('<').trim_end_matches('>');
+                let id = value.rsplit('-').next()?;
+                id.parse::<usize>().ok()?.checked_sub(1)
+            })
+            .next();
+        let (head, body) = split_headers(http);
+        let status = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|status| status.parse().ok())
+            .unwrap_or(0);
+        parts.push(BatchPart {
+            index,
+            status,
+            body: String::from(body.trim_end()),
+        });
+    }
+    parts
+}
+
+/// Turn a sub-response into the outcome of its sub-request.
+#[allow(clippy::result_large_err)]
+fn outcome(client: &Client, expect: Expect, part: BatchPart) -> Result<BatchResponse, Error> {
+    match part.status {
+        200..=299 => {}
+        304 | 412 => return Err(Error::PreconditionFailed),
+        status => {
+            let message = json::from_str::<json::Value>(&part.body)
+                .ok()
+                .and_then(|body| body["error"]["message"].as_str().map(String::from))
+                .unwrap_or(part.body);
+            return Err(Error::BatchItem { status, message });
+        }
+    }
+
+    match expect {
+        Expect::Empty => Ok(BatchResponse::Deleted),
+        Expect::Object => {
+            let resource = json::from_str::<ObjectResource>(&part.body)?;
+            let object = Object::new(client.clone(), resource, false);
+            Ok(BatchResponse::Object(Box::new(object)))
+        }
+        Expect::AclEntry => {
+            let resource = json::from_str::<ObjectAclResource>(&part.body)?;
+            Ok(BatchResponse::AclEntry(resource.into()))
+        }
+    }
+}
+
+impl Client {
+    /// Start a new batch of requests.
+    pub fn batch(&self) -> Batch {
+        Batch::new(self.clone())
+    }
+}
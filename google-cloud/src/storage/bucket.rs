@@ -1,13 +1,250 @@
+use std::collections::HashMap;
+
+use chrono::Duration;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
-use crate::storage::api::object::ObjectResource;
-use crate::storage::{Client, Error, Object};
+use crate::storage::api::bucket::{
+    BucketBilling, BucketCors, BucketEncryption, BucketIamConfig, BucketLifecycle, BucketLogging,
+    BucketPatchResource, BucketRetentionPolicy, BucketUniformLevelAccess, BucketVersioning,
+    BucketWebsite,
+};
+use crate::storage::api::object::{
+    ComposeRequest, ObjectInsertResource, ObjectResource, ObjectResources,
+};
+use crate::storage::checksum::Checksums;
+use crate::storage::{
+    random_token, Client, ComposeSource, Conditions, EncryptionKey, Error, LifecycleRule, Object,
+    ObjectConfig, PostPolicy, PostPolicyConfig, PublicAccessPrevention, SignedUrlConfig,
+    UploadConfig,
+};
+
+/// Represents a CORS rule of a bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorsRule {
+    pub(crate) origins: Vec<String>,
+    pub(crate) methods: Vec<String>,
+    pub(crate) response_headers: Vec<String>,
+    pub(crate) max_age_seconds: Option<i32>,
+}
+
+impl CorsRule {
+    /// Allow requests from the given origin (`*` means any origin).
+    pub fn origin(mut self, origin: impl Into<String>) -> CorsRule {
+        self.origins.push(origin.into());
+        self
+    }
+
+    /// Allow requests using the given HTTP method.
+    pub fn method(mut self, method: impl Into<String>) -> CorsRule {
+        self.methods.push(method.into());
+        self
+    }
+
+    /// Allow the given response header to be shared across origins.
+    pub fn response_header(mut self, header: impl Into<String>) -> CorsRule {
+        self.response_headers.push(header.into());
+        self
+    }
+
+    /// Set how long browsers may cache preflight responses, in seconds.
+    pub fn max_age_seconds(mut self, seconds: i32) -> CorsRule {
+        self.max_age_seconds = Some(seconds);
+        self
+    }
+}
+
+impl From<CorsRule> for BucketCors {
+    fn from(rule: CorsRule) -> BucketCors {
+        BucketCors {
+            origin: rule.origins,
+            method: rule.methods,
+            response_header: rule.response_headers,
+            max_age_seconds: rule.max_age_seconds,
+        }
+    }
+}
+
+/// Represents the bucket's configuration.
+///
+/// When patching a bucket, only the settings explicitly set are modified
+/// (and lists, like lifecycle or CORS rules, are replaced as a whole).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BucketConfig {
+    pub(crate) location: Option<String>,
+    pub(crate) storage_class: Option<String>,
+    pub(crate) versioning: Option<bool>,
+    pub(crate) lifecycle_rules: Option<Vec<LifecycleRule>>,
+    pub(crate) retention_period: Option<Duration>,
+    pub(crate) cors: Option<Vec<CorsRule>>,
+    pub(crate) labels: HashMap<String, String>,
+    pub(crate) website: Option<BucketWebsite>,
+    pub(crate) logging: Option<BucketLogging>,
+    pub(crate) default_kms_key_name: Option<String>,
+    pub(crate) requester_pays: Option<bool>,
+    pub(crate) uniform_bucket_level_access: Option<bool>,
+    pub(crate) public_access_prevention: Option<PublicAccessPrevention>,
+}
+
+impl BucketConfig {
+    /// Set the bucket's location (like `US`, `EU` or `europe-west1`).
+    ///
+    /// This can only be set when creating the bucket.
+    pub fn location(mut self, location: impl Into<String>) -> BucketConfig {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Set the default storage class of the bucket's objects (like `STANDARD` or `NEARLINE`).
+    pub fn storage_class(mut self, storage_class: impl Into<String>) -> BucketConfig {
+        self.storage_class = Some(storage_class.into());
+        self
+    }
+
+    /// Enable or disable object versioning.
+    pub fn versioning(mut self, enabled: bool) -> BucketConfig {
+        self.versioning = Some(enabled);
+        self
+    }
+
+    /// Add a lifecycle rule.
+    pub fn lifecycle_rule(mut self, rule: LifecycleRule) -> BucketConfig {
+        self.lifecycle_rules.get_or_insert_with(Vec::new).push(rule);
+        self
+    }
+
+    /// Set the minimum duration objects must be retained for.
+    pub fn retention_period(mut self, period: Duration) -> BucketConfig {
+        self.retention_period = Some(period);
+        self
+    }
+
+    /// Add a CORS rule.
+    pub fn cors_rule(mut self, rule: CorsRule) -> BucketConfig {
+        self.cors.get_or_insert_with(Vec::new).push(rule);
+        self
+    }
+
+    /// Add a label.
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> BucketConfig {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Configure the bucket to act as a static website, with the given index and 404 pages.
+    pub fn website(
+        mut self,
+        main_page_suffix: impl Into<String>,
+        not_found_page: impl Into<String>,
+    ) -> BucketConfig {
+        self.website = Some(BucketWebsite {
+            main_page_suffix: Some(main_page_suffix.into()),
+            not_found_page: Some(not_found_page.into()),
+        });
+        self
+    }
+
+    /// Store the bucket's access logs into another bucket, with the given object name prefix.
+    pub fn logging(
+        mut self,
+        log_bucket: impl Into<String>,
+        log_object_prefix: impl Into<String>,
+    ) -> BucketConfig {
+        self.logging = Some(BucketLogging {
+            log_bucket: log_bucket.into(),
+            log_object_prefix: Some(log_object_prefix.into()),
+        });
+        self
+    }
+
+    /// Encrypt new objects with the given Cloud KMS key by default.
+    pub fn default_kms_key_name(mut self, kms_key_name: impl Into<String>) -> BucketConfig {
+        self.default_kms_key_name = Some(kms_key_name.into());
+        self
+    }
+
+    /// Make requesters pay for accessing the bucket.
+    pub fn requester_pays(mut self, enabled: bool) -> BucketConfig {
+        self.requester_pays = Some(enabled);
+        self
+    }
+
+    /// Enable or disable uniform bucket-level access (which disables object ACLs).
+    pub fn uniform_bucket_level_access(mut self, enabled: bool) -> BucketConfig {
+        self.uniform_bucket_level_access = Some(enabled);
+        self
+    }
+
+    /// Set the public access prevention setting.
+    pub fn public_access_prevention(mut self, prevention: PublicAccessPrevention) -> BucketConfig {
+        self.public_access_prevention = Some(prevention);
+        self
+    }
+}
+
+impl From<BucketConfig> for BucketPatchResource {
+    fn from(config: BucketConfig) -> BucketPatchResource {
+        let iam_configuration = if config.uniform_bucket_level_access.is_some()
+            || config.public_access_prevention.is_some()
+        {
+            Some(BucketIamConfig {
+                uniform_bucket_level_access: config.uniform_bucket_level_access.map(|enabled| {
+                    BucketUniformLevelAccess {
+                        enabled,
+                        locked_time: None,
+                    }
+                }),
+                public_access_prevention: config
+                    .public_access_prevention
+                    .map(|prevention| String::from(prevention.as_str())),
+            })
+        } else {
+            None
+        };
+
+        BucketPatchResource {
+            name: None,
+            location: config.location,
+            storage_class: config.storage_class,
+            versioning: config
+                .versioning
+                .map(|enabled| BucketVersioning { enabled }),
+            lifecycle: config.lifecycle_rules.map(|rules| BucketLifecycle {
+                rule: rules.into_iter().map(Into::into).collect(),
+            }),
+            retention_policy: config.retention_period.map(|period| BucketRetentionPolicy {
+                retention_period: period.num_seconds().to_string(),
+                effective_time: None,
+                is_locked: None,
+            }),
+            cors: config
+                .cors
+                .map(|rules| rules.into_iter().map(Into::into).collect()),
+            labels: if config.labels.is_empty() {
+                None
+            } else {
+                Some(config.labels)
+            },
+            website: config.website,
+            logging: config.logging,
+            encryption: config
+                .default_kms_key_name
+                .map(|default_kms_key_name| BucketEncryption {
+                    default_kms_key_name,
+                }),
+            billing: config
+                .requester_pays
+                .map(|requester_pays| BucketBilling { requester_pays }),
+            iam_configuration,
+        }
+    }
+}
 
 /// Represents a Cloud Storage bucket.
 #[derive(Clone)]
 pub struct Bucket {
     pub(crate) client: Client,
     pub(crate) name: String,
+    pub(crate) encryption_key: Option<EncryptionKey>,
 }
 
 impl Bucket {
@@ -15,9 +252,25 @@ impl Bucket {
         Bucket {
             client,
             name: name.into(),
+            encryption_key: None,
         }
     }
 
+    /// Use the given customer-supplied key to encrypt new objects (and to read existing ones).
+    ///
+    /// Object handles obtained from this bucket handle inherit the key.
+    pub fn with_encryption_key(mut self, key: EncryptionKey) -> Bucket {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Create a handle to an object of the bucket, inheriting its encryption key.
+    pub(crate) fn object_handle(&self, resource: ObjectResource, pinned: bool) -> Object {
+        let mut object = Object::new(self.client.clone(), resource, pinned);
+        object.encryption_key = self.encryption_key.clone();
+        object
+    }
+
     /// Get the bucket's name.
     pub fn name(&self) -> &str {
         self.name.as_str()
@@ -29,81 +282,361 @@ impl Bucket {
         name: &str,
         data: impl Into<Vec<u8>>,
         mime_type: impl AsRef<str>,
+    ) -> Result<Object, Error> {
+        self.create_object_with_conditions(name, data, mime_type, Conditions::default())
+            .await
+    }
+
+    /// Insert a new object into the bucket, if the given conditions are met.
+    ///
+    /// Use `Conditions::if_generation_match(0)` to only create the object if it does not exist yet.
+    pub async fn create_object_with_conditions(
+        &mut self,
+        name: &str,
+        data: impl Into<Vec<u8>>,
+        mime_type: impl AsRef<str>,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
+        let config = ObjectConfig::default().content_type(mime_type.as_ref());
+        let config = UploadConfig::default().object_config(config);
+        self.upload_with_conditions(name, data, config, conditions)
+            .await
+    }
+
+    /// Upload a new object into the bucket, along with its metadata and checksums.
+    ///
+    /// Cloud Storage rejects the upload if the data does not match the checksums.
+    pub async fn upload(
+        &mut self,
+        name: &str,
+        data: impl Into<Vec<u8>>,
+        config: UploadConfig,
+    ) -> Result<Object, Error> {
+        self.upload_with_conditions(name, data, config, Conditions::default())
+            .await
+    }
+
+    /// Upload a new object into the bucket, if the given conditions are met.
+    pub async fn upload_with_conditions(
+        &mut self,
+        name: &str,
+        data: impl Into<Vec<u8>>,
+        config: UploadConfig,
+        conditions: Conditions,
     ) -> Result<Object, Error> {
         let client = &mut self.client;
         let inner = &client.client;
         let uri = format!(
             "{}/b/{}/o",
-            Client::UPLOAD_ENDPOINT,
+            client.upload_endpoint(),
             utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
         );
 
         let data = data.into();
+        let checksums = Checksums::compute(&data, config.crc32c, config.md5);
+        let mime_type = config
+            .object
+            .content_type
+            .clone()
+            .unwrap_or_else(|| String::from("application/octet-stream"));
+        let metadata = ObjectInsertResource {
+            name: String::from(name),
+            crc32c: checksums.crc32c,
+            md5_hash: checksums.md5,
+            config: config.object.into(),
+        };
+        let (boundary, body) = multipart_body(&json::to_vec(&metadata)?, &mime_type, data)?;
+
         let token = client.token_manager.lock().await.token().await?;
-        let request = inner
+        let mut request = inner
             .post(uri.as_str())
-            .query(&[("uploadType", "media"), ("name", name)])
+            .query(&[("uploadType", "multipart")])
+            .query(&conditions.query())
             .header("authorization", token)
-            .header("content-type", mime_type.as_ref())
-            .header("content-length", data.len())
-            .body(data)
-            .send();
-        let response = request.await?;
-        let string = response.error_for_status()?.text().await?;
+            .header(
+                "content-type",
+                format!("multipart/related; boundary={}", boundary),
+            )
+            .header("content-length", body.len());
+        if let Some(key) = self.encryption_key.as_ref() {
+            request = key.apply(request);
+        }
+        let response = request.body(body).send().await?;
+        let string = Client::check_status(response)?.text().await?;
         let resource = json::from_str::<ObjectResource>(string.as_str())?;
 
-        Ok(Object::new(
-            client.clone(),
-            self.name.clone(),
-            resource.name,
-        ))
+        Ok(self.object_handle(resource, false))
+    }
+
+    /// Compose up to 32 objects of the bucket into a new one, by concatenating them in order.
+    ///
+    /// If the handle has a customer-supplied key, the sources must all be encrypted with it.
+    pub async fn compose(
+        &mut self,
+        name: &str,
+        sources: Vec<ComposeSource>,
+        config: ObjectConfig,
+    ) -> Result<Object, Error> {
+        self.compose_with_conditions(name, sources, config, Conditions::default())
+            .await
+    }
+
+    /// Compose objects of the bucket into a new one, if the given conditions (on the destination object) are met.
+    pub async fn compose_with_conditions(
+        &mut self,
+        name: &str,
+        sources: Vec<ComposeSource>,
+        config: ObjectConfig,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
+        if sources.is_empty() || sources.len() > 32 {
+            return Err(Error::InvalidArgument(format!(
+                "a composition requires between 1 and 32 source objects (got {})",
+                sources.len(),
+            )));
+        }
+
+        let client = &mut self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}/o/{}/compose",
+            client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+            utf8_percent_encode(name, NON_ALPHANUMERIC),
+        );
+        let body = ComposeRequest {
+            kind: String::from("storage#composeRequest"),
+            source_objects: sources.into_iter().map(Into::into).collect(),
+            destination: config.into(),
+        };
+
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .post(uri.as_str())
+            .query(&conditions.query())
+            .header("authorization", token);
+        if let Some(key) = self.encryption_key.as_ref() {
+            request = key.apply(request);
+        }
+        let response = request.json(&body).send().await?;
+        let resource = Client::check_status(response)?.json().await?;
+
+        Ok(self.object_handle(resource, false))
+    }
+
+    /// List all objects stored in the bucket.
+    pub async fn objects(&mut self) -> Result<Vec<Object>, Error> {
+        self.objects_with_prefix("").await
+    }
+
+    /// List all objects stored in the bucket whose names start with the given prefix.
+    pub async fn objects_with_prefix(&mut self, prefix: &str) -> Result<Vec<Object>, Error> {
+        let client = &mut self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}/o",
+            client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        );
+
+        let mut resources = Vec::new();
+        let mut page_token = None;
+        loop {
+            let token = client.token_manager.lock().await.token().await?;
+            let mut request = inner
+                .get(uri.as_str())
+                .query(&[("prefix", prefix)])
+                .header("authorization", token);
+            if let Some(page_token) = page_token.as_ref() {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+            let response = request.send().await?;
+            let page = Client::check_status(response)?
+                .json::<ObjectResources>()
+                .await?;
+            resources.extend(page.items);
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+
+        let objects = resources
+            .into_iter()
+            .map(|resource| self.object_handle(resource, false))
+            .collect();
+
+        Ok(objects)
     }
 
     /// Get an object stored in the bucket.
     pub async fn object(&mut self, name: &str) -> Result<Object, Error> {
+        self.object_with_conditions(name, Conditions::default())
+            .await
+    }
+
+    /// Get an object stored in the bucket, if the given conditions are met.
+    pub async fn object_with_conditions(
+        &mut self,
+        name: &str,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
+        self.fetch_object(name, None, conditions).await
+    }
+
+    /// Get a specific generation of an object stored in the bucket.
+    ///
+    /// The returned handle is pinned to that generation: reading or deleting it targets
+    /// that version of the object, even if it is no longer the live one.
+    pub async fn object_generation(
+        &mut self,
+        name: &str,
+        generation: i64,
+    ) -> Result<Object, Error> {
+        self.fetch_object(name, Some(generation), Conditions::default())
+            .await
+    }
+
+    async fn fetch_object(
+        &mut self,
+        name: &str,
+        generation: Option<i64>,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
         let client = &mut self.client;
         let inner = &client.client;
         let uri = format!(
             "{}/b/{}/o/{}",
-            Client::ENDPOINT,
+            client.endpoint(),
             utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
             utf8_percent_encode(name, NON_ALPHANUMERIC),
         );
+        let mut query = conditions.query();
+        if let Some(generation) = generation {
+            query.push(("generation", generation.to_string()));
+        }
 
         let token = client.token_manager.lock().await.token().await?;
         let request = inner
             .get(uri.as_str())
+            .query(&query)
             .header("authorization", token)
             .send();
         let response = request.await?;
-        let string = response.error_for_status()?.text().await?;
+        let string = Client::check_status(response)?.text().await?;
         let resource = json::from_str::<ObjectResource>(string.as_str())?;
 
-        Ok(Object::new(
-            client.clone(),
-            self.name.clone(),
-            resource.name,
-        ))
+        Ok(self.object_handle(resource, generation.is_some()))
+    }
+
+    /// Generate a V4 signed URL to the bucket itself (to list its objects, for instance).
+    pub async fn signed_url(&mut self, config: SignedUrlConfig) -> Result<String, Error> {
+        self.client.signed_url(&self.name, None, config).await
+    }
+
+    /// Generate a V4 signed URL to an object of the bucket, which may not exist yet (to upload it, for instance).
+    pub async fn signed_object_url(
+        &mut self,
+        name: &str,
+        config: SignedUrlConfig,
+    ) -> Result<String, Error> {
+        self.client.signed_url(&self.name, Some(name), config).await
+    }
+
+    /// Generate a V4 signed POST policy, allowing browsers to upload an object using an HTML form.
+    ///
+    /// The object name may contain `${filename}`, to be replaced by the name of the uploaded file.
+    pub async fn signed_post_policy(
+        &mut self,
+        name: &str,
+        config: PostPolicyConfig,
+    ) -> Result<PostPolicy, Error> {
+        self.client
+            .signed_post_policy(&self.name, name, config)
+            .await
+    }
+
+    /// Update the bucket's configuration.
+    pub async fn patch(&mut self, config: BucketConfig) -> Result<(), Error> {
+        self.patch_with_conditions(config, Conditions::default())
+            .await
+    }
+
+    /// Update the bucket's configuration, if the given (metageneration) conditions are met.
+    pub async fn patch_with_conditions(
+        &mut self,
+        config: BucketConfig,
+        conditions: Conditions,
+    ) -> Result<(), Error> {
+        let client = &mut self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}",
+            client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        );
+
+        let body = BucketPatchResource::from(config);
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .patch(uri.as_str())
+            .query(&conditions.query())
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        Client::check_status(response)?;
+
+        Ok(())
     }
 
     /// Delete the bucket.
     pub async fn delete(self) -> Result<(), Error> {
+        self.delete_with_conditions(Conditions::default()).await
+    }
+
+    /// Delete the bucket, if the given (metageneration) conditions are met.
+    pub async fn delete_with_conditions(self, conditions: Conditions) -> Result<(), Error> {
         let client = self.client;
-        let inner = client.client;
         let uri = format!(
             "{}/b/{}",
-            Client::ENDPOINT,
+            client.endpoint(),
             utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
         );
+        let inner = client.client;
 
         let token = client.token_manager.lock().await.token().await?;
         let request = inner
             .delete(uri.as_str())
+            .query(&conditions.query())
             .header("authorization", token)
             .send();
         let response = request.await?;
-        response.error_for_status()?;
+        Client::check_status(response)?;
 
         Ok(())
     }
 }
+
+/// Build a `multipart/related` body, made of the object's JSON metadata followed by its data.
+///
+/// Returns the boundary delimiting the parts, along with the body.
+#[allow(clippy::result_large_err)]
+fn multipart_body(
+    metadata: &[u8],
+    mime_type: &str,
+    data: Vec<u8>,
+) -> Result<(String, Vec<u8>), Error> {
+    let boundary = random_token(16)?;
+
+    let mut body = Vec::with_capacity(metadata.len() + data.len() + 256);
+    body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+    body.extend_from_slice(b"content-type: application/json; charset=UTF-8\r\n\r\n");
+    body.extend_from_slice(metadata);
+    body.extend_from_slice(format!("\r\n--{}\r\n", boundary).as_bytes());
+    body.extend_from_slice(format!("content-type: {}\r\n\r\n", mime_type).as_bytes());
+    body.extend_from_slice(&data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    Ok((boundary, body))
+}
@@ -0,0 +1,135 @@
+use reqwest::header::HeaderMap;
+
+use crate::storage::Error;
+
+/// Incrementally computes the checksums of object data, as it is streamed.
+#[derive(Clone)]
+pub(crate) struct Hasher {
+    crc32c: Option<u32>,
+    md5: Option<md5::Context>,
+}
+
+impl Hasher {
+    pub(crate) fn new(crc32c: bool, md5: bool) -> Hasher {
+        Hasher {
+            crc32c: if crc32c { Some(0) } else { None },
+            md5: if md5 { Some(md5::Context::new()) } else { None },
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        if let Some(crc) = self.crc32c.as_mut() {
+            *crc = crc32c::crc32c_append(*crc, data);
+        }
+        if let Some(ctx) = self.md5.as_mut() {
+            ctx.consume(data);
+        }
+    }
+
+    pub(crate) fn finish(self) -> Checksums {
+        Checksums {
+            crc32c: self.crc32c.map(|crc| base64::encode(crc.to_be_bytes())),
+            md5: self.md5.map(|ctx| base64::encode(ctx.compute().0)),
+        }
+    }
+}
+
+/// The base64-encoded checksums of some object data, as used by Cloud Storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Checksums {
+    /// The big-endian CRC32C checksum.
+    pub(crate) crc32c: Option<String>,
+    /// The MD5 hash.
+    pub(crate) md5: Option<String>,
+}
+
+impl Checksums {
+    /// Compute the checksums of some data, at once.
+    pub(crate) fn compute(data: &[u8], crc32c: bool, md5: bool) -> Checksums {
+        let mut hasher = Hasher::new(crc32c, md5);
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// Parse the checksums from the (possibly repeated) `x-goog-hash` headers of a response.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Checksums {
+        let mut checksums = Checksums::default();
+        let values = headers
+            .get_all("x-goog-hash")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for value in values {
+            let mut parts = value.trim().splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some("crc32c"), Some(hash)) => checksums.crc32c = Some(String::from(hash)),
+                (Some("md5"), Some(hash)) => checksums.md5 = Some(String::from(hash)),
+                _ => {}
+            }
+        }
+        checksums
+    }
+
+    /// Check the checksums computed from some data against these ones (every algorithm both have).
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn check(&self, computed: &Checksums) -> Result<(), Error> {
+        let pairs = [
+            ("crc32c", self.crc32c.as_ref(), computed.crc32c.as_ref()),
+            ("md5", self.md5.as_ref(), computed.md5.as_ref()),
+        ];
+        for (algorithm, expected, computed) in pairs.iter() {
+            match (expected, computed) {
+                (Some(expected), Some(computed)) if expected != computed => {
+                    return Err(Error::ChecksumMismatch {
+                        algorithm: String::from(*algorithm),
+                        expected: String::from(expected.as_str()),
+                        computed: String::from(computed.as_str()),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Check some data against these checksums (preferring CRC32C, as composite objects have no MD5).
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn verify(&self, data: &[u8]) -> Result<(), Error> {
+        let (algorithm, expected, computed) = match (self.crc32c.as_ref(), self.md5.as_ref()) {
+            (Some(expected), _) => {
+                let computed = Checksums::compute(data, true, false).crc32c;
+                ("crc32c", expected, computed.unwrap_or_default())
+            }
+            (None, Some(expected)) => {
+                let computed = Checksums::compute(data, false, true).md5;
+                ("md5", expected, computed.unwrap_or_default())
+            }
+            (None, None) => return Ok(()),
+        };
+
+        if *expected == computed {
+            Ok(())
+        } else {
+            Err(Error::ChecksumMismatch {
+                algorithm: String::from(algorithm),
+                expected: expected.clone(),
+                computed,
+            })
+        }
+    }
+}
+
+/// Whether Cloud Storage decompressed the data it served (decompressive transcoding).
+///
+/// In that case, the reported checksums are those of the stored (compressed) data,
+/// so the received data cannot be checked against them.
+pub(crate) fn is_transcoded(headers: &HeaderMap) -> bool {
+    let encoding = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.eq_ignore_ascii_case("gzip"))
+            .unwrap_or(false)
+    };
+    encoding("x-goog-stored-content-encoding") && !encoding("content-encoding")
+}
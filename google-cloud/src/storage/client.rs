@@ -2,46 +2,77 @@ use std::env;
 use std::fs::File;
 use std::sync::Arc;
 
-use json::json;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::StatusCode;
 use tokio::sync::Mutex;
+use tonic::transport::Channel;
 
 use crate::authorize::{ApplicationCredentials, TokenManager};
-use crate::storage::api::bucket::{BucketResource, BucketResources};
-use crate::storage::{Bucket, Error};
+use crate::storage::api::bucket::{BucketPatchResource, BucketResource, BucketResources};
+use crate::storage::api::bytestream::byte_stream_client::ByteStreamClient;
+use crate::storage::{Bucket, BucketConfig, Error};
 
 /// The Cloud Storage client, tied to a specific project.
 #[derive(Clone)]
 pub struct Client {
     pub(crate) project_name: String,
+    pub(crate) base_url: String,
     pub(crate) client: Arc<reqwest::Client>,
+    /// The ByteStream service, if object data goes through gRPC.
+    pub(crate) byte_stream: Option<ByteStreamClient<Channel>>,
     pub(crate) token_manager: Arc<Mutex<TokenManager>>,
 }
 
 impl Client {
-    #[allow(unused)]
     pub(crate) const DOMAIN_NAME: &'static str = "storage.googleapis.com";
-    pub(crate) const ENDPOINT: &'static str = "https://storage.googleapis.com/storage/v1";
-    /// Cloud Storage uses a slightly different endpoint for uploads.
-    pub(crate) const UPLOAD_ENDPOINT: &'static str =
-        "https://storage.googleapis.com/upload/storage/v1";
+    pub(crate) const BASE_URL: &'static str = "https://storage.googleapis.com";
+    /// Endpoint of the IAM credentials API, used to sign blobs without a private key.
+    pub(crate) const IAM_CREDENTIALS_ENDPOINT: &'static str =
+        "https://iamcredentials.googleapis.com/v1";
     pub(crate) const SCOPES: [&'static str; 2] = [
         "https://www.googleapis.com/auth/cloud-platform",
         "https://www.googleapis.com/auth/devstorage.full_control",
     ];
-    #[allow(dead_code)]
-    pub(crate) fn uri(uri: &str) -> String {
-        if uri.starts_with('/') {
-            format!("{}{}", Client::ENDPOINT, uri)
-        } else {
-            format!("{}/{}", Client::ENDPOINT, uri)
+
+    /// The endpoint of the JSON API.
+    pub(crate) fn endpoint(&self) -> String {
+        format!("{}/storage/v1", self.base_url)
+    }
+
+    /// Cloud Storage uses a slightly different endpoint for uploads.
+    pub(crate) fn upload_endpoint(&self) -> String {
+        format!("{}/upload/storage/v1", self.base_url)
+    }
+
+    /// Endpoint accepting batches of JSON API requests.
+    pub(crate) fn batch_endpoint(&self) -> String {
+        format!("{}/batch/storage/v1", self.base_url)
+    }
+
+    /// Turns unsuccessful responses into errors.
+    ///
+    /// Unmet request preconditions are reported as `Error::PreconditionFailed`.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn check_status(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+        match response.status() {
+            StatusCode::PRECONDITION_FAILED | StatusCode::NOT_MODIFIED => {
+                Err(Error::PreconditionFailed)
+            }
+            _ => Ok(response.error_for_status()?),
         }
     }
 
     /// Create a new client for the specified project.
     ///
     /// Credentials are looked up in the `GOOGLE_APPLICATION_CREDENTIALS` environment variable.
+    ///
+    /// If the `STORAGE_EMULATOR_HOST` environment variable is set, the client connects
+    /// to that emulator instead (without authentication).
     pub async fn new(project_name: impl Into<String>) -> Result<Client, Error> {
+        if let Ok(host) = env::var("STORAGE_EMULATOR_HOST") {
+            return Client::from_emulator_host(project_name, host).await;
+        }
+
         let path = env::var("GOOGLE_APPLICATION_CREDENTIALS")?;
         let file = File::open(path)?;
         let creds = json::from_reader(file)?;
@@ -62,6 +93,8 @@ impl Client {
         Ok(Client {
             client: Arc::new(client),
             project_name: project_name.into(),
+            base_url: String::from(Client::BASE_URL),
+            byte_stream: None,
             token_manager: Arc::new(Mutex::new(TokenManager::new(
                 creds,
                 Client::SCOPES.as_ref(),
@@ -69,12 +102,36 @@ impl Client {
         })
     }
 
+    /// Create a new client for the specified project, connected to an emulator (without authentication).
+    ///
+    /// The host can be given with a scheme (like `http://localhost:4443`), and defaults to HTTP otherwise.
+    pub async fn from_emulator_host(
+        project_name: impl Into<String>,
+        host: impl Into<String>,
+    ) -> Result<Client, Error> {
+        let host = host.into();
+        let base_url = if host.contains("://") {
+            host
+        } else {
+            format!("http://{}", host)
+        };
+        let client = reqwest::Client::builder().build()?;
+
+        Ok(Client {
+            client: Arc::new(client),
+            project_name: project_name.into(),
+            base_url: String::from(base_url.trim_end_matches('/')),
+            byte_stream: None,
+            token_manager: Arc::new(Mutex::new(TokenManager::Insecure)),
+        })
+    }
+
     /// Get a handle to a specific bucket.
     pub async fn bucket(&mut self, name: &str) -> Result<Bucket, Error> {
         let inner = &self.client;
         let uri = format!(
             "{}/b/{}",
-            Client::ENDPOINT,
+            self.endpoint(),
             utf8_percent_encode(name, NON_ALPHANUMERIC),
         );
 
@@ -84,8 +141,7 @@ impl Client {
             .header("authorization", token)
             .send();
         let response = request.await?;
-        let bucket = response
-            .error_for_status()?
+        let bucket = Client::check_status(response)?
             .json::<BucketResource>()
             .await?;
 
@@ -95,7 +151,7 @@ impl Client {
     /// List all existing buckets of the current project.
     pub async fn buckets(&mut self) -> Result<Vec<Bucket>, Error> {
         let inner = &self.client;
-        let uri = format!("{}/b", Client::ENDPOINT);
+        let uri = format!("{}/b", self.endpoint());
 
         let token = self.token_manager.lock().await.token().await?;
         let request = inner
@@ -104,8 +160,7 @@ impl Client {
             .header("authorization", token)
             .send();
         let response = request.await?;
-        let resources = response
-            .error_for_status()?
+        let resources = Client::check_status(response)?
             .json::<BucketResources>()
             .await?;
 
@@ -120,13 +175,23 @@ impl Client {
 
     /// Create a new bucket and get a handle to it.
     pub async fn create_bucket(&mut self, name: &str) -> Result<Bucket, Error> {
+        self.create_bucket_with_config(name, BucketConfig::default())
+            .await
+    }
+
+    /// Create a new bucket with the given configuration and get a handle to it.
+    pub async fn create_bucket_with_config(
+        &mut self,
+        name: &str,
+        config: BucketConfig,
+    ) -> Result<Bucket, Error> {
         let inner = &self.client;
-        let uri = format!("{}/b", Client::ENDPOINT);
+        let uri = format!("{}/b", self.endpoint());
 
-        let body = json!({
-            "kind": "storage#bucket",
-            "name": name,
-        });
+        let body = BucketPatchResource {
+            name: Some(String::from(name)),
+            ..config.into()
+        };
         let token = self.token_manager.lock().await.token().await?;
         let request = inner
             .post(uri.as_str())
@@ -135,8 +200,7 @@ impl Client {
             .json(&body)
             .send();
         let response = request.await?;
-        let bucket = response
-            .error_for_status()?
+        let bucket = Client::check_status(response)?
             .json::<BucketResource>()
             .await?;
 
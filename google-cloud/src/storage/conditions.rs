@@ -0,0 +1,53 @@
+/// Represents the preconditions of a Cloud Storage request.
+///
+/// When any of the conditions are not met, the request fails with `Error::PreconditionFailed`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Conditions {
+    pub(crate) if_generation_match: Option<i64>,
+    pub(crate) if_generation_not_match: Option<i64>,
+    pub(crate) if_metageneration_match: Option<i64>,
+    pub(crate) if_metageneration_not_match: Option<i64>,
+}
+
+impl Conditions {
+    /// Only proceed if the object's current generation matches the given value.
+    ///
+    /// Setting it to `0` makes the request succeed only if there is no live version of the object.
+    pub fn if_generation_match(mut self, generation: i64) -> Conditions {
+        self.if_generation_match = Some(generation);
+        self
+    }
+
+    /// Only proceed if the object's current generation does not match the given value.
+    pub fn if_generation_not_match(mut self, generation: i64) -> Conditions {
+        self.if_generation_not_match = Some(generation);
+        self
+    }
+
+    /// Only proceed if the resource's current metageneration matches the given value.
+    pub fn if_metageneration_match(mut self, metageneration: i64) -> Conditions {
+        self.if_metageneration_match = Some(metageneration);
+        self
+    }
+
+    /// Only proceed if the resource's current metageneration does not match the given value.
+    pub fn if_metageneration_not_match(mut self, metageneration: i64) -> Conditions {
+        self.if_metageneration_not_match = Some(metageneration);
+        self
+    }
+
+    /// Get the query parameters corresponding to these conditions.
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let params = [
+            ("ifGenerationMatch", self.if_generation_match),
+            ("ifGenerationNotMatch", self.if_generation_not_match),
+            ("ifMetagenerationMatch", self.if_metageneration_match),
+            ("ifMetagenerationNotMatch", self.if_metageneration_not_match),
+        ];
+
+        params
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| (*name, value.to_string())))
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use reqwest::RequestBuilder;
+use ring::digest::{digest, SHA256};
+
+use crate::storage::api::object::ObjectResource;
+use crate::storage::{fill_random, Error};
+
+/// Represents a customer-supplied AES-256 encryption key (CSEK).
+///
+/// Cloud Storage does not keep the key: objects encrypted with it can only be read
+/// (or rewritten) by supplying the same key again.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey {
+    pub(crate) key: [u8; 32],
+}
+
+impl EncryptionKey {
+    /// Create a key from its raw bytes.
+    pub fn new(key: [u8; 32]) -> EncryptionKey {
+        EncryptionKey { key }
+    }
+
+    /// Create a key from its base64 encoding.
+    #[allow(clippy::result_large_err)]
+    pub fn from_base64(key: &str) -> Result<EncryptionKey, Error> {
+        let bytes = base64::decode(key).map_err(|err| {
+            Error::InvalidArgument(format!("invalid base64 encryption key: {}", err))
+        })?;
+        if bytes.len() != 32 {
+            return Err(Error::InvalidArgument(format!(
+                "encryption keys must be 32 bytes long (got {})",
+                bytes.len(),
+            )));
+        }
+
+        let mut key = [0; 32];
+        key.copy_from_slice(&bytes);
+        Ok(EncryptionKey { key })
+    }
+
+    /// Generate a new random key.
+    #[allow(clippy::result_large_err)]
+    pub fn generate() -> Result<EncryptionKey, Error> {
+        let mut key = [0; 32];
+        fill_random(&mut key)?;
+        Ok(EncryptionKey { key })
+    }
+
+    /// Get the base64 encoding of the key.
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.key)
+    }
+
+    /// Get the base64 encoding of the key's SHA-256 hash, which identifies it without revealing it.
+    pub fn sha256(&self) -> String {
+        base64::encode(digest(&SHA256, &self.key))
+    }
+
+    /// Add the headers encrypting (or decrypting) the target of the request with this key.
+    pub(crate) fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        self.apply_with_prefix(request, "x-goog-encryption")
+    }
+
+    /// Add the headers decrypting the source object of a copy or rewrite with this key.
+    pub(crate) fn apply_source(&self, request: RequestBuilder) -> RequestBuilder {
+        self.apply_with_prefix(request, "x-goog-copy-source-encryption")
+    }
+
+    fn apply_with_prefix(&self, request: RequestBuilder, prefix: &str) -> RequestBuilder {
+        request
+            .header(format!("{}-algorithm", prefix).as_str(), "AES256")
+            .header(format!("{}-key", prefix).as_str(), self.to_base64())
+            .header(format!("{}-key-sha256", prefix).as_str(), self.sha256())
+    }
+
+    /// Check that an object is encrypted with this key, before attempting to use it.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn check(&self, resource: &ObjectResource) -> Result<(), Error> {
+        match resource.customer_encryption.as_ref() {
+            Some(encryption) if encryption.key_sha256 != self.sha256() => {
+                Err(Error::InvalidArgument(format!(
+                    "object '{}' is encrypted with another key (SHA-256: {})",
+                    resource.name, encryption.key_sha256,
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The key itself is never printed, only its hash.
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("sha256", &self.sha256())
+            .finish()
+    }
+}
@@ -0,0 +1,66 @@
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+use tonic::Request;
+
+use crate::authorize::TLS_CERTS;
+use crate::storage::api::bytestream::byte_stream_client::ByteStreamClient;
+use crate::storage::{Client, Error};
+
+impl Client {
+    /// Use gRPC (the ByteStream API) for object data, through `Object::reader` and `Bucket::writer`.
+    ///
+    /// Metadata operations keep going through the JSON API.
+    pub async fn with_grpc(self) -> Result<Client, Error> {
+        let tls_config = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(TLS_CERTS))
+            .domain_name(Client::DOMAIN_NAME);
+
+        let channel = Channel::from_static(Client::BASE_URL)
+            .tls_config(tls_config)?
+            .connect()
+            .await?;
+
+        Ok(self.with_grpc_channel(channel))
+    }
+
+    /// Use gRPC for object data, through the given channel (like one to a local ByteStream server).
+    pub fn with_grpc_channel(mut self, channel: Channel) -> Client {
+        self.byte_stream = Some(ByteStreamClient::new(channel));
+        self
+    }
+
+    /// Does object data go through gRPC ?
+    pub fn uses_grpc(&self) -> bool {
+        self.byte_stream.is_some()
+    }
+
+    /// Wrap a gRPC message into an authenticated request.
+    pub(crate) async fn grpc_request<T>(&self, message: T) -> Result<Request<T>, Error> {
+        let mut request = Request::new(message);
+        let token = self.token_manager.lock().await.token().await?;
+        if !token.is_empty() {
+            let token = token
+                .parse()
+                .map_err(|_| Error::InvalidArgument(String::from("invalid access token")))?;
+            request.metadata_mut().insert("authorization", token);
+        }
+        Ok(request)
+    }
+}
+
+/// Get the ByteStream resource name of an object's generation.
+///
+/// Object names are percent-encoded, like in JSON API paths.
+pub(crate) fn object_resource_name(bucket: &str, name: &str, generation: i64) -> String {
+    format!(
+        "projects/_/buckets/{}/objects/{}#{}",
+        bucket,
+        utf8_percent_encode(name, NON_ALPHANUMERIC),
+        generation,
+    )
+}
+
+/// Get the ByteStream resource name of a resumable upload session.
+pub(crate) fn upload_resource_name(upload_id: &str) -> String {
+    format!("uploads/{}", upload_id)
+}
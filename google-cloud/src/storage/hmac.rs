@@ -0,0 +1,270 @@
+use chrono::{DateTime, Utc};
+use json::json;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::api::hmac_key::{
+    HmacKeyMetadataResource, HmacKeyResource, HmacKeyUpdateResource, HmacKeysMetadataResources,
+    ServiceAccountResource,
+};
+use crate::storage::{Client, Error};
+
+/// Represents the state of an HMAC key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HmacKeyState {
+    /// The key can be used to authenticate requests.
+    Active,
+    /// The key cannot be used, but can be reactivated (or deleted).
+    Inactive,
+    /// The key has been deleted (it cannot be used nor reactivated).
+    Deleted,
+}
+
+/// Represents the metadata of an HMAC key (everything but its secret).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HmacKeyMetadata {
+    pub(crate) resource: HmacKeyMetadataResource,
+}
+
+impl HmacKeyMetadata {
+    /// Get the key's access ID (used to identify it in requests).
+    pub fn access_id(&self) -> &str {
+        self.resource.access_id.as_str()
+    }
+
+    /// Get the email address of the service account the key authenticates as.
+    pub fn service_account_email(&self) -> &str {
+        self.resource.service_account_email.as_str()
+    }
+
+    /// Get the ID of the project owning the key.
+    pub fn project_id(&self) -> &str {
+        self.resource.project_id.as_str()
+    }
+
+    /// Get the key's state.
+    pub fn state(&self) -> HmacKeyState {
+        self.resource.state
+    }
+
+    /// Get the key's creation time.
+    pub fn time_created(&self) -> Option<DateTime<Utc>> {
+        let time = DateTime::parse_from_rfc3339(&self.resource.time_created).ok()?;
+        Some(time.with_timezone(&Utc))
+    }
+
+    /// Get the time of the key's last update.
+    pub fn updated(&self) -> Option<DateTime<Utc>> {
+        let time = DateTime::parse_from_rfc3339(&self.resource.updated).ok()?;
+        Some(time.with_timezone(&Utc))
+    }
+
+    /// Get the key's etag.
+    pub fn etag(&self) -> &str {
+        self.resource.etag.as_str()
+    }
+}
+
+/// Represents a newly created HMAC key, along with its secret.
+///
+/// The secret is only ever returned at creation: it cannot be retrieved afterwards.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HmacKey {
+    pub(crate) metadata: HmacKeyMetadata,
+    pub(crate) secret: String,
+}
+
+impl HmacKey {
+    /// Get the key's metadata.
+    pub fn metadata(&self) -> &HmacKeyMetadata {
+        &self.metadata
+    }
+
+    /// Get the key's secret.
+    pub fn secret(&self) -> &str {
+        self.secret.as_str()
+    }
+}
+
+/// The secret itself is never printed.
+impl std::fmt::Debug for HmacKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("HmacKey")
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
+
+impl Client {
+    fn hmac_keys_uri(&self) -> String {
+        format!(
+            "{}/projects/{}/hmacKeys",
+            self.endpoint(),
+            utf8_percent_encode(&self.project_name, NON_ALPHANUMERIC),
+        )
+    }
+
+    fn hmac_key_uri(&self, access_id: &str) -> String {
+        format!(
+            "{}/{}",
+            self.hmac_keys_uri(),
+            utf8_percent_encode(access_id, NON_ALPHANUMERIC),
+        )
+    }
+
+    /// Create a new HMAC key for the given service account.
+    pub async fn create_hmac_key(&mut self, service_account_email: &str) -> Result<HmacKey, Error> {
+        let inner = &self.client;
+        let uri = self.hmac_keys_uri();
+
+        let token = self.token_manager.lock().await.token().await?;
+        let request = inner
+            .post(uri.as_str())
+            .query(&[("serviceAccountEmail", service_account_email)])
+            .header("authorization", token)
+            .json(&json!({}))
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?
+            .json::<HmacKeyResource>()
+            .await?;
+
+        Ok(HmacKey {
+            metadata: HmacKeyMetadata {
+                resource: resource.metadata,
+            },
+            secret: resource.secret,
+        })
+    }
+
+    /// List the (non-deleted) HMAC keys of the current project.
+    pub async fn hmac_keys(&mut self) -> Result<Vec<HmacKeyMetadata>, Error> {
+        self.list_hmac_keys(None).await
+    }
+
+    /// List the (non-deleted) HMAC keys of the given service account.
+    pub async fn hmac_keys_for_service_account(
+        &mut self,
+        service_account_email: &str,
+    ) -> Result<Vec<HmacKeyMetadata>, Error> {
+        self.list_hmac_keys(Some(service_account_email)).await
+    }
+
+    async fn list_hmac_keys(
+        &mut self,
+        service_account_email: Option<&str>,
+    ) -> Result<Vec<HmacKeyMetadata>, Error> {
+        let inner = &self.client;
+        let uri = self.hmac_keys_uri();
+
+        let mut resources = Vec::new();
+        let mut page_token = None;
+        loop {
+            let token = self.token_manager.lock().await.token().await?;
+            let mut request = inner.get(uri.as_str()).header("authorization", token);
+            if let Some(email) = service_account_email {
+                request = request.query(&[("serviceAccountEmail", email)]);
+            }
+            if let Some(page_token) = page_token.as_ref() {
+                request = request.query(&[("pageToken", page_token)]);
+            }
+            let response = request.send().await?;
+            let page = Client::check_status(response)?
+                .json::<HmacKeysMetadataResources>()
+                .await?;
+            resources.extend(page.items);
+            match page.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+
+        let keys = resources
+            .into_iter()
+            .map(|resource| HmacKeyMetadata { resource })
+            .collect();
+
+        Ok(keys)
+    }
+
+    /// Get the metadata of an HMAC key, by access ID.
+    pub async fn hmac_key(&mut self, access_id: &str) -> Result<HmacKeyMetadata, Error> {
+        let inner = &self.client;
+        let uri = self.hmac_key_uri(access_id);
+
+        let token = self.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json().await?;
+
+        Ok(HmacKeyMetadata { resource })
+    }
+
+    /// Activate or deactivate an HMAC key, by access ID.
+    pub async fn update_hmac_key(
+        &mut self,
+        access_id: &str,
+        state: HmacKeyState,
+    ) -> Result<HmacKeyMetadata, Error> {
+        let inner = &self.client;
+        let uri = self.hmac_key_uri(access_id);
+
+        let body = HmacKeyUpdateResource { state, etag: None };
+        let token = self.token_manager.lock().await.token().await?;
+        let request = inner
+            .put(uri.as_str())
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json().await?;
+
+        Ok(HmacKeyMetadata { resource })
+    }
+
+    /// Delete an HMAC key, by access ID.
+    ///
+    /// Only inactive keys can be deleted.
+    pub async fn delete_hmac_key(&mut self, access_id: &str) -> Result<(), Error> {
+        let inner = &self.client;
+        let uri = self.hmac_key_uri(access_id);
+
+        let token = self.token_manager.lock().await.token().await?;
+        let request = inner
+            .delete(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        Client::check_status(response)?;
+
+        Ok(())
+    }
+
+    /// Get the email address of the project's Cloud Storage service agent.
+    ///
+    /// This is the identity Cloud Storage acts as, when using KMS keys or publishing notifications.
+    pub async fn service_account_email(&mut self) -> Result<String, Error> {
+        let inner = &self.client;
+        let uri = format!(
+            "{}/projects/{}/serviceAccount",
+            self.endpoint(),
+            utf8_percent_encode(&self.project_name, NON_ALPHANUMERIC),
+        );
+
+        let token = self.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?
+            .json::<ServiceAccountResource>()
+            .await?;
+
+        Ok(resource.email_address)
+    }
+}
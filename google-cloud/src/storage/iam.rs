@@ -0,0 +1,247 @@
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{Bucket, BucketConfig, Client, Error};
+
+/// Represents a condition restricting when an IAM binding applies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IamCondition {
+    pub(crate) title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+    pub(crate) expression: String,
+}
+
+impl IamCondition {
+    /// Create a new condition, from a title and a CEL expression.
+    pub fn new(title: impl Into<String>, expression: impl Into<String>) -> IamCondition {
+        IamCondition {
+            title: title.into(),
+            description: None,
+            expression: expression.into(),
+        }
+    }
+
+    /// Set the condition's description.
+    pub fn description(mut self, description: impl Into<String>) -> IamCondition {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Get the condition's title.
+    pub fn title(&self) -> &str {
+        self.title.as_str()
+    }
+
+    /// Get the condition's CEL expression.
+    pub fn expression(&self) -> &str {
+        self.expression.as_str()
+    }
+}
+
+/// Represents a binding of members to a role, within an IAM policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IamBinding {
+    pub(crate) role: String,
+    #[serde(default)]
+    pub(crate) members: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) condition: Option<IamCondition>,
+}
+
+impl IamBinding {
+    /// Create a new binding for the given role (like `roles/storage.objectViewer`).
+    pub fn new(role: impl Into<String>) -> IamBinding {
+        IamBinding {
+            role: role.into(),
+            members: Vec::new(),
+            condition: None,
+        }
+    }
+
+    /// Add a member (like `user:jane@example.com` or `allUsers`) to the binding.
+    pub fn member(mut self, member: impl Into<String>) -> IamBinding {
+        self.members.push(member.into());
+        self
+    }
+
+    /// Only apply the binding when the given condition is met.
+    pub fn condition(mut self, condition: IamCondition) -> IamBinding {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Get the binding's role.
+    pub fn role(&self) -> &str {
+        self.role.as_str()
+    }
+
+    /// Get the binding's members.
+    pub fn members(&self) -> &[String] {
+        self.members.as_slice()
+    }
+
+    /// Get the binding's condition, if any.
+    pub fn get_condition(&self) -> Option<&IamCondition> {
+        self.condition.as_ref()
+    }
+}
+
+/// Represents the IAM policy of a bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IamPolicy {
+    #[serde(default)]
+    pub(crate) version: i32,
+    #[serde(default)]
+    pub(crate) bindings: Vec<IamBinding>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) etag: String,
+}
+
+impl IamPolicy {
+    /// Get the policy's version.
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Get the policy's bindings.
+    pub fn bindings(&self) -> &[IamBinding] {
+        self.bindings.as_slice()
+    }
+
+    /// Add a binding to the policy.
+    ///
+    /// Adding a conditional binding upgrades the policy to version 3, as required by the API.
+    pub fn add_binding(&mut self, binding: IamBinding) {
+        if binding.condition.is_some() {
+            self.version = 3;
+        }
+        self.bindings.push(binding);
+    }
+
+    /// Remove all bindings for the given role.
+    pub fn remove_role(&mut self, role: &str) {
+        self.bindings.retain(|binding| binding.role != role);
+    }
+}
+
+/// Represents the public access prevention setting of a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PublicAccessPrevention {
+    /// Public access is allowed, unless prevented by an organization policy.
+    Inherited,
+    /// Public access to the bucket and its objects is prevented.
+    Enforced,
+}
+
+impl PublicAccessPrevention {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            PublicAccessPrevention::Inherited => "inherited",
+            PublicAccessPrevention::Enforced => "enforced",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestIamPermissionsResponse {
+    #[serde(default)]
+    permissions: Vec<String>,
+}
+
+impl Bucket {
+    fn iam_uri(&self, path: &str) -> String {
+        format!(
+            "{}/b/{}/{}",
+            self.client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+            path,
+        )
+    }
+
+    /// Get the IAM policy of the bucket.
+    ///
+    /// Version 3 policies are requested, so conditional bindings are included.
+    pub async fn iam_policy(&mut self) -> Result<IamPolicy, Error> {
+        let uri = self.iam_uri("iam");
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .query(&[("optionsRequestedPolicyVersion", "3")])
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let policy = Client::check_status(response)?.json().await?;
+
+        Ok(policy)
+    }
+
+    /// Replace the IAM policy of the bucket, and get the resulting policy back.
+    ///
+    /// The policy's etag (if obtained from `Bucket::iam_policy`) guards against concurrent modifications.
+    pub async fn set_iam_policy(&mut self, policy: &IamPolicy) -> Result<IamPolicy, Error> {
+        let uri = self.iam_uri("iam");
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .put(uri.as_str())
+            .header("authorization", token)
+            .json(policy)
+            .send();
+        let response = request.await?;
+        let policy = Client::check_status(response)?.json().await?;
+
+        Ok(policy)
+    }
+
+    /// Test which of the given permissions (like `storage.buckets.get`) the caller has on the bucket.
+    pub async fn test_iam_permissions(
+        &mut self,
+        permissions: &[&str],
+    ) -> Result<Vec<String>, Error> {
+        let uri = self.iam_uri("iam/testPermissions");
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let query: Vec<_> = permissions
+            .iter()
+            .map(|permission| ("permissions", *permission))
+            .collect();
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .query(&query)
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let response = Client::check_status(response)?
+            .json::<TestIamPermissionsResponse>()
+            .await?;
+
+        Ok(response.permissions)
+    }
+
+    /// Enable or disable uniform bucket-level access (which disables object ACLs).
+    pub async fn set_uniform_bucket_level_access(&mut self, enabled: bool) -> Result<(), Error> {
+        let config = BucketConfig::default().uniform_bucket_level_access(enabled);
+        self.patch(config).await
+    }
+
+    /// Change the public access prevention setting of the bucket.
+    pub async fn set_public_access_prevention(
+        &mut self,
+        prevention: PublicAccessPrevention,
+    ) -> Result<(), Error> {
+        let config = BucketConfig::default().public_access_prevention(prevention);
+        self.patch(config).await
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+
+use crate::storage::api::bucket::{BucketRule, BucketRuleAction, BucketRuleCondition};
+
+/// Represents a lifecycle rule of a bucket: an action, taken on objects meeting all of its conditions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleRule {
+    pub(crate) action: BucketRuleAction,
+    pub(crate) condition: BucketRuleCondition,
+}
+
+impl LifecycleRule {
+    /// Create a rule deleting matching objects.
+    pub fn delete() -> LifecycleRule {
+        LifecycleRule::new("Delete", None)
+    }
+
+    /// Create a rule changing the storage class of matching objects.
+    pub fn set_storage_class(storage_class: impl Into<String>) -> LifecycleRule {
+        LifecycleRule::new("SetStorageClass", Some(storage_class.into()))
+    }
+
+    /// Create a rule aborting incomplete multipart uploads (made through the XML API).
+    ///
+    /// Only the `age`, `matches_prefix` and `matches_suffix` conditions apply to such rules.
+    pub fn abort_incomplete_multipart_upload() -> LifecycleRule {
+        LifecycleRule::new("AbortIncompleteMultipartUpload", None)
+    }
+
+    fn new(action_type: &str, storage_class: Option<String>) -> LifecycleRule {
+        LifecycleRule {
+            action: BucketRuleAction {
+                action_type: String::from(action_type),
+                storage_class,
+            },
+            condition: BucketRuleCondition::default(),
+        }
+    }
+
+    /// Only match objects older than the given number of days.
+    pub fn age(mut self, days: i32) -> LifecycleRule {
+        self.condition.age = Some(days);
+        self
+    }
+
+    /// Only match objects created before the given date (at midnight UTC).
+    pub fn created_before(mut self, date: NaiveDate) -> LifecycleRule {
+        self.condition.created_before = Some(date.format("%Y-%m-%d").to_string());
+        self
+    }
+
+    /// Only match live objects (`true`) or noncurrent versions (`false`).
+    pub fn is_live(mut self, is_live: bool) -> LifecycleRule {
+        self.condition.is_live = Some(is_live);
+        self
+    }
+
+    /// Only match objects of the given storage class (can be called multiple times).
+    pub fn matches_storage_class(mut self, storage_class: impl Into<String>) -> LifecycleRule {
+        self.condition
+            .matches_storage_class
+            .get_or_insert_with(Vec::new)
+            .push(storage_class.into());
+        self
+    }
+
+    /// Only match objects having at least the given number of newer versions.
+    pub fn num_newer_versions(mut self, count: i32) -> LifecycleRule {
+        self.condition.num_newer_versions = Some(count);
+        self
+    }
+
+    /// Only match objects whose custom time is before the given date.
+    pub fn custom_time_before(mut self, date: NaiveDate) -> LifecycleRule {
+        self.condition.custom_time_before = Some(date.format("%Y-%m-%d").to_string());
+        self
+    }
+
+    /// Only match objects whose custom time is at least the given number of days ago.
+    pub fn days_since_custom_time(mut self, days: i32) -> LifecycleRule {
+        self.condition.days_since_custom_time = Some(days);
+        self
+    }
+
+    /// Only match noncurrent versions that became noncurrent at least the given number of days ago.
+    pub fn days_since_noncurrent_time(mut self, days: i32) -> LifecycleRule {
+        self.condition.days_since_noncurrent_time = Some(days);
+        self
+    }
+
+    /// Only match noncurrent versions that became noncurrent before the given date.
+    pub fn noncurrent_time_before(mut self, date: NaiveDate) -> LifecycleRule {
+        self.condition.noncurrent_time_before = Some(date.format("%Y-%m-%d").to_string());
+        self
+    }
+
+    /// Only match objects whose names start with the given prefix (can be called multiple times).
+    pub fn matches_prefix(mut self, prefix: impl Into<String>) -> LifecycleRule {
+        self.condition
+            .matches_prefix
+            .get_or_insert_with(Vec::new)
+            .push(prefix.into());
+        self
+    }
+
+    /// Only match objects whose names end with the given suffix (can be called multiple times).
+    pub fn matches_suffix(mut self, suffix: impl Into<String>) -> LifecycleRule {
+        self.condition
+            .matches_suffix
+            .get_or_insert_with(Vec::new)
+            .push(suffix.into());
+        self
+    }
+}
+
+impl From<LifecycleRule> for BucketRule {
+    fn from(rule: LifecycleRule) -> BucketRule {
+        BucketRule {
+            action: rule.action,
+            condition: rule.condition,
+        }
+    }
+}
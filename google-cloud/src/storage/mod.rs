@@ -1,11 +1,62 @@
-mod api;
+use std::io;
+
+use ring::rand::{SecureRandom, SystemRandom};
+
+mod acl;
+pub(crate) mod api;
+pub(crate) mod batch;
 mod bucket;
+pub(crate) mod checksum;
 mod client;
+mod conditions;
+mod encryption;
+mod grpc;
+mod hmac;
+mod iam;
+mod lifecycle;
+mod notification;
 mod object;
+mod retention;
+pub(crate) mod signing;
+mod stream;
+pub mod sync;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transfer;
 
+pub use self::acl::*;
+pub use self::batch::{Batch, BatchResponse};
 pub use self::bucket::*;
 pub use self::client::*;
+pub use self::conditions::*;
+pub use self::encryption::*;
+pub use self::hmac::*;
+pub use self::iam::*;
+pub use self::lifecycle::*;
+pub use self::notification::*;
 pub use self::object::*;
+pub use self::retention::*;
+pub use self::signing::{
+    PostPolicy, PostPolicyCondition, PostPolicyConfig, SignedUrlConfig, UrlStyle,
+};
+pub use self::stream::*;
+pub use self::transfer::*;
 
 /// The error type for the Cloud Storage module.
 pub type Error = crate::error::Error;
+
+/// Fill a buffer with random bytes from the system's secure generator.
+#[allow(clippy::result_large_err)]
+pub(crate) fn fill_random(bytes: &mut [u8]) -> Result<(), Error> {
+    SystemRandom::new()
+        .fill(bytes)
+        .map_err(|_| Error::IO(io::Error::other("could not generate random bytes")))
+}
+
+/// Generate a random hexadecimal token out of `len` random bytes (for names or multipart boundaries).
+#[allow(clippy::result_large_err)]
+pub(crate) fn random_token(len: usize) -> Result<String, Error> {
+    let mut bytes = vec![0; len];
+    fill_random(&mut bytes)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
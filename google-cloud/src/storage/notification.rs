@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ConvertError;
+use crate::storage::api::notification::{
+    NotificationInsertResource, NotificationResource, NotificationResources,
+};
+use crate::storage::api::object::ObjectResource;
+use crate::storage::{Bucket, Client, Error};
+
+/// The prefix of the full resource names of Pub/Sub topics.
+const PUBSUB_RESOURCE_PREFIX: &str = "//pubsub.googleapis.com/";
+
+/// Represents the type of an object change event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EventType {
+    /// A new object (or a new generation of an existing object) was successfully created.
+    ObjectFinalize,
+    /// The metadata of an existing object changed.
+    ObjectMetadataUpdate,
+    /// An object was permanently deleted.
+    ObjectDelete,
+    /// The live version of an object became a noncurrent version.
+    ObjectArchive,
+}
+
+impl EventType {
+    fn parse(event_type: &str) -> Option<EventType> {
+        match event_type {
+            "OBJECT_FINALIZE" => Some(EventType::ObjectFinalize),
+            "OBJECT_METADATA_UPDATE" => Some(EventType::ObjectMetadataUpdate),
+            "OBJECT_DELETE" => Some(EventType::ObjectDelete),
+            "OBJECT_ARCHIVE" => Some(EventType::ObjectArchive),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the payload format of notification messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum PayloadFormat {
+    /// The message data is the JSON representation of the object.
+    #[default]
+    #[serde(rename = "JSON_API_V1")]
+    JsonApiV1,
+    /// The message has no data, only attributes.
+    #[serde(rename = "NONE")]
+    None,
+}
+
+/// Represents the configuration of a new notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotificationConfig {
+    pub(crate) topic: String,
+    pub(crate) event_types: Vec<EventType>,
+    pub(crate) object_name_prefix: Option<String>,
+    pub(crate) custom_attributes: HashMap<String, String>,
+    pub(crate) payload_format: PayloadFormat,
+}
+
+impl NotificationConfig {
+    /// Publish notifications to the given Pub/Sub topic.
+    ///
+    /// The topic can either be a short name (of a topic within the client's project),
+    /// or a fully-qualified one (`projects/{project}/topics/{topic}`, optionally prefixed
+    /// by `//pubsub.googleapis.com/`).
+    pub fn new(topic: impl Into<String>) -> NotificationConfig {
+        NotificationConfig {
+            topic: topic.into(),
+            event_types: Vec::new(),
+            object_name_prefix: None,
+            custom_attributes: HashMap::new(),
+            payload_format: PayloadFormat::default(),
+        }
+    }
+
+    /// Only notify of the given event type (can be called multiple times, all types are notified by default).
+    pub fn event_type(mut self, event_type: EventType) -> NotificationConfig {
+        self.event_types.push(event_type);
+        self
+    }
+
+    /// Only notify of changes to objects whose names start with the given prefix.
+    pub fn object_name_prefix(mut self, prefix: impl Into<String>) -> NotificationConfig {
+        self.object_name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Attach a custom attribute to notification messages.
+    pub fn custom_attribute(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> NotificationConfig {
+        self.custom_attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the payload format of notification messages.
+    pub fn payload_format(mut self, payload_format: PayloadFormat) -> NotificationConfig {
+        self.payload_format = payload_format;
+        self
+    }
+
+    /// Build the resource sent to create the notification, with the topic's full resource name.
+    pub(crate) fn into_resource(self, project_name: &str) -> NotificationInsertResource {
+        let topic = if self.topic.starts_with(PUBSUB_RESOURCE_PREFIX) {
+            self.topic
+        } else if self.topic.contains('/') {
+            format!("{}{}", PUBSUB_RESOURCE_PREFIX, self.topic)
+        } else {
+            format!(
+                "{}projects/{}/topics/{}",
+                PUBSUB_RESOURCE_PREFIX, project_name, self.topic
+            )
+        };
+
+        NotificationInsertResource {
+            topic,
+            event_types: if self.event_types.is_empty() {
+                None
+            } else {
+                Some(self.event_types)
+            },
+            custom_attributes: if self.custom_attributes.is_empty() {
+                None
+            } else {
+                Some(self.custom_attributes)
+            },
+            payload_format: self.payload_format,
+            object_name_prefix: self.object_name_prefix,
+        }
+    }
+}
+
+/// Represents a Pub/Sub notification configured on a bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub(crate) resource: NotificationResource,
+}
+
+impl Notification {
+    /// Get the notification's ID.
+    pub fn id(&self) -> &str {
+        self.resource.id.as_str()
+    }
+
+    /// Get the Pub/Sub topic notifications are published to.
+    pub fn topic(&self) -> &str {
+        self.resource.topic.as_str()
+    }
+
+    /// Get the notified event types (all types are notified if empty).
+    pub fn event_types(&self) -> &[EventType] {
+        self.resource.event_types.as_deref().unwrap_or_default()
+    }
+
+    /// Get the prefix of the names of the objects whose changes are notified, if any.
+    pub fn object_name_prefix(&self) -> Option<&str> {
+        self.resource.object_name_prefix.as_deref()
+    }
+
+    /// Get the custom attributes attached to notification messages, if any.
+    pub fn custom_attributes(&self) -> Option<&HashMap<String, String>> {
+        self.resource.custom_attributes.as_ref()
+    }
+
+    /// Get the payload format of notification messages.
+    pub fn payload_format(&self) -> PayloadFormat {
+        self.resource.payload_format
+    }
+}
+
+impl Bucket {
+    fn notifications_uri(&self) -> String {
+        format!(
+            "{}/b/{}/notificationConfigs",
+            self.client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        )
+    }
+
+    /// List the Pub/Sub notifications configured on the bucket.
+    pub async fn notifications(&mut self) -> Result<Vec<Notification>, Error> {
+        let uri = self.notifications_uri();
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resources = Client::check_status(response)?
+            .json::<NotificationResources>()
+            .await?;
+
+        let notifications = resources
+            .items
+            .into_iter()
+            .map(|resource| Notification { resource })
+            .collect();
+
+        Ok(notifications)
+    }
+
+    /// Configure a new Pub/Sub notification on the bucket.
+    ///
+    /// The bucket's service agent must be allowed to publish to the topic.
+    pub async fn create_notification(
+        &mut self,
+        config: NotificationConfig,
+    ) -> Result<Notification, Error> {
+        let uri = self.notifications_uri();
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let body = config.into_resource(&client.project_name);
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .post(uri.as_str())
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?.json().await?;
+
+        Ok(Notification { resource })
+    }
+
+    /// Delete a Pub/Sub notification of the bucket, by ID.
+    pub async fn delete_notification(&mut self, id: &str) -> Result<(), Error> {
+        let uri = format!(
+            "{}/{}",
+            self.notifications_uri(),
+            utf8_percent_encode(id, NON_ALPHANUMERIC),
+        );
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .delete(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        Client::check_status(response)?;
+
+        Ok(())
+    }
+}
+
+/// Represents an object change event, as received from a bucket's Pub/Sub notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectChangeEvent {
+    pub(crate) event_type: EventType,
+    pub(crate) notification_config: String,
+    pub(crate) bucket: String,
+    pub(crate) object: String,
+    pub(crate) generation: i64,
+    pub(crate) event_time: DateTime<Utc>,
+    pub(crate) overwrote_generation: Option<i64>,
+    pub(crate) overwritten_by_generation: Option<i64>,
+    pub(crate) payload: Option<ObjectResource>,
+}
+
+impl ObjectChangeEvent {
+    /// Parse an event from the attributes and data of a notification message.
+    #[allow(clippy::result_large_err)]
+    pub fn parse(attributes: &HashMap<String, String>, data: &[u8]) -> Result<Self, Error> {
+        let attribute = |name: &str| {
+            attributes
+                .get(name)
+                .ok_or_else(|| ConvertError::MissingProperty(String::from(name)))
+        };
+        let invalid = |name: &str, value: &str| {
+            Error::InvalidArgument(format!("invalid `{}` attribute: {}", name, value))
+        };
+        let generation = |name: &str| match attributes.get(name) {
+            Some(value) => value.parse().map(Some).map_err(|_| invalid(name, value)),
+            None => Ok(None),
+        };
+
+        let event_type = attribute("eventType")?;
+        let event_type =
+            EventType::parse(event_type).ok_or_else(|| invalid("eventType", event_type))?;
+        let event_time = attribute("eventTime")?;
+        let event_time = DateTime::parse_from_rfc3339(event_time)
+            .map_err(|_| invalid("eventTime", event_time))?
+            .with_timezone(&Utc);
+        let payload = match attributes.get("payloadFormat").map(String::as_str) {
+            Some("JSON_API_V1") if !data.is_empty() => Some(json::from_slice(data)?),
+            _ => None,
+        };
+
+        Ok(ObjectChangeEvent {
+            event_type,
+            notification_config: attribute("notificationConfig")?.clone(),
+            bucket: attribute("bucketId")?.clone(),
+            object: attribute("objectId")?.clone(),
+            generation: generation("objectGeneration")?.unwrap_or_default(),
+            event_time,
+            overwrote_generation: generation("overwroteGeneration")?,
+            overwritten_by_generation: generation("overwrittenByGeneration")?,
+            payload,
+        })
+    }
+
+    /// Get the event's type.
+    pub fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    /// Get the name of the notification configuration that triggered the event.
+    pub fn notification_config(&self) -> &str {
+        self.notification_config.as_str()
+    }
+
+    /// Get the name of the bucket of the changed object.
+    pub fn bucket(&self) -> &str {
+        self.bucket.as_str()
+    }
+
+    /// Get the name of the changed object.
+    pub fn object(&self) -> &str {
+        self.object.as_str()
+    }
+
+    /// Get the generation of the changed object.
+    pub fn generation(&self) -> i64 {
+        self.generation
+    }
+
+    /// Get the time of the event.
+    pub fn event_time(&self) -> DateTime<Utc> {
+        self.event_time
+    }
+
+    /// Get the generation of the object replaced by this one, if any (finalize events only).
+    pub fn overwrote_generation(&self) -> Option<i64> {
+        self.overwrote_generation
+    }
+
+    /// Get the generation of the object that replaced this one, if any (archive and delete events only).
+    pub fn overwritten_by_generation(&self) -> Option<i64> {
+        self.overwritten_by_generation
+    }
+
+    /// Get the size of the object, in bytes (if the message had a JSON payload).
+    pub fn size(&self) -> Option<u64> {
+        let payload = self.payload.as_ref()?;
+        payload.size.parse().ok()
+    }
+
+    /// Get the content type of the object (if the message had a JSON payload).
+    pub fn content_type(&self) -> Option<&str> {
+        let payload = self.payload.as_ref()?;
+        Some(payload.content_type.as_str())
+    }
+
+    /// Get the base64-encoded CRC32C checksum of the object (if the message had a JSON payload).
+    pub fn crc32c(&self) -> Option<&str> {
+        let payload = self.payload.as_ref()?;
+        Some(payload.crc32c.as_str())
+    }
+
+    /// Get the custom metadata of the object (if the message had a JSON payload).
+    pub fn metadata(&self) -> Option<&HashMap<String, String>> {
+        let payload = self.payload.as_ref()?;
+        payload.metadata.as_ref()
+    }
+}
+
+#[cfg(feature = "pubsub")]
+impl std::convert::TryFrom<&crate::pubsub::Message> for ObjectChangeEvent {
+    type Error = Error;
+
+    fn try_from(message: &crate::pubsub::Message) -> Result<ObjectChangeEvent, Error> {
+        ObjectChangeEvent::parse(message.attributes(), message.data())
+    }
+}
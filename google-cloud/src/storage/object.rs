@@ -1,28 +1,299 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use json::json;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
-use crate::storage::{Client, Error};
+use crate::storage::api::object::{
+    ComposeSourceObject, ComposeSourcePreconditions, ObjectPatchResource, ObjectResource,
+    RewriteResponse,
+};
+use crate::storage::checksum::{self, Checksums};
+use crate::storage::{Client, Conditions, EncryptionKey, Error, SignedUrlConfig};
+
+/// Represents the object's configuration (its modifiable metadata).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectConfig {
+    pub(crate) content_type: Option<String>,
+    pub(crate) content_encoding: Option<String>,
+    pub(crate) content_disposition: Option<String>,
+    pub(crate) content_language: Option<String>,
+    pub(crate) cache_control: Option<String>,
+    pub(crate) metadata: HashMap<String, String>,
+}
+
+impl ObjectConfig {
+    /// Set the object's content type.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> ObjectConfig {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Set the object's content encoding.
+    pub fn content_encoding(mut self, content_encoding: impl Into<String>) -> ObjectConfig {
+        self.content_encoding = Some(content_encoding.into());
+        self
+    }
+
+    /// Set the object's content disposition.
+    pub fn content_disposition(mut self, content_disposition: impl Into<String>) -> ObjectConfig {
+        self.content_disposition = Some(content_disposition.into());
+        self
+    }
+
+    /// Set the object's content language.
+    pub fn content_language(mut self, content_language: impl Into<String>) -> ObjectConfig {
+        self.content_language = Some(content_language.into());
+        self
+    }
+
+    /// Set the object's cache control directives.
+    pub fn cache_control(mut self, cache_control: impl Into<String>) -> ObjectConfig {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
+
+    /// Attach a custom metadata entry to the object.
+    pub fn metadata(mut self, name: impl Into<String>, value: impl Into<String>) -> ObjectConfig {
+        self.metadata.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl From<ObjectConfig> for ObjectPatchResource {
+    fn from(config: ObjectConfig) -> ObjectPatchResource {
+        ObjectPatchResource {
+            content_type: config.content_type,
+            content_encoding: config.content_encoding,
+            content_disposition: config.content_disposition,
+            content_language: config.content_language,
+            cache_control: config.cache_control,
+            metadata: if config.metadata.is_empty() {
+                None
+            } else {
+                Some(config.metadata)
+            },
+            storage_class: None,
+            temporary_hold: None,
+            event_based_hold: None,
+            retention: None,
+        }
+    }
+}
+
+/// Represents the configuration of an object upload.
+///
+/// By default, a CRC32C checksum of the data is computed and sent along with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadConfig {
+    pub(crate) object: ObjectConfig,
+    pub(crate) crc32c: bool,
+    pub(crate) md5: bool,
+}
+
+impl UploadConfig {
+    /// Set the metadata of the uploaded object.
+    pub fn object_config(mut self, config: ObjectConfig) -> UploadConfig {
+        self.object = config;
+        self
+    }
+
+    /// Enable or disable sending a CRC32C checksum of the data.
+    pub fn crc32c(mut self, enabled: bool) -> UploadConfig {
+        self.crc32c = enabled;
+        self
+    }
+
+    /// Enable or disable sending an MD5 hash of the data.
+    pub fn md5(mut self, enabled: bool) -> UploadConfig {
+        self.md5 = enabled;
+        self
+    }
+}
+
+impl Default for UploadConfig {
+    fn default() -> UploadConfig {
+        UploadConfig {
+            object: ObjectConfig::default(),
+            crc32c: true,
+            md5: false,
+        }
+    }
+}
+
+/// Represents the configuration of an object rewrite.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewriteConfig {
+    pub(crate) storage_class: Option<String>,
+    pub(crate) kms_key_name: Option<String>,
+    pub(crate) max_bytes_per_call: Option<i64>,
+    pub(crate) encryption_key: Option<EncryptionKey>,
+}
+
+impl RewriteConfig {
+    /// Set the storage class of the destination object.
+    pub fn storage_class(mut self, storage_class: impl Into<String>) -> RewriteConfig {
+        self.storage_class = Some(storage_class.into());
+        self
+    }
+
+    /// Encrypt the destination object with the given Cloud KMS key.
+    pub fn kms_key_name(mut self, kms_key_name: impl Into<String>) -> RewriteConfig {
+        self.kms_key_name = Some(kms_key_name.into());
+        self
+    }
+
+    /// Encrypt the destination object with the given customer-supplied key.
+    ///
+    /// Rewriting an object onto itself with a new key rotates its encryption key.
+    pub fn encryption_key(mut self, key: EncryptionKey) -> RewriteConfig {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Limit the number of bytes rewritten by each rewrite call (must be a multiple of 1 MiB).
+    pub fn max_bytes_per_call(mut self, max_bytes: i64) -> RewriteConfig {
+        self.max_bytes_per_call = Some(max_bytes);
+        self
+    }
+}
+
+/// Represents a source object of a composition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComposeSource {
+    pub(crate) name: String,
+    pub(crate) generation: Option<i64>,
+    pub(crate) if_generation_match: Option<i64>,
+}
+
+impl ComposeSource {
+    /// Use the live version of the named object.
+    pub fn new(name: impl Into<String>) -> ComposeSource {
+        ComposeSource {
+            name: name.into(),
+            generation: None,
+            if_generation_match: None,
+        }
+    }
+
+    /// Use a specific generation of the object.
+    pub fn generation(mut self, generation: i64) -> ComposeSource {
+        self.generation = Some(generation);
+        self
+    }
+
+    /// Only proceed if the object's current generation matches the given value.
+    pub fn if_generation_match(mut self, generation: i64) -> ComposeSource {
+        self.if_generation_match = Some(generation);
+        self
+    }
+}
+
+impl From<&str> for ComposeSource {
+    fn from(name: &str) -> ComposeSource {
+        ComposeSource::new(name)
+    }
+}
+
+impl From<String> for ComposeSource {
+    fn from(name: String) -> ComposeSource {
+        ComposeSource::new(name)
+    }
+}
+
+/// Uses the object's generation (as of when its handle was obtained) as a precondition.
+impl From<&Object> for ComposeSource {
+    fn from(object: &Object) -> ComposeSource {
+        let source = ComposeSource::new(object.name.as_str());
+        if object.pinned {
+            source.generation(object.generation())
+        } else {
+            source.if_generation_match(object.generation())
+        }
+    }
+}
 
-/// Represents a Cloud Storage bucket.
+impl From<ComposeSource> for ComposeSourceObject {
+    fn from(source: ComposeSource) -> ComposeSourceObject {
+        ComposeSourceObject {
+            name: source.name,
+            generation: source.generation.map(|generation| generation.to_string()),
+            object_preconditions: source.if_generation_match.map(|generation| {
+                ComposeSourcePreconditions {
+                    if_generation_match: generation.to_string(),
+                }
+            }),
+        }
+    }
+}
+
+/// Represents a Cloud Storage object.
 #[derive(Clone)]
 pub struct Object {
     pub(crate) client: Client,
     pub(crate) name: String,
     pub(crate) bucket: String,
+    pub(crate) pinned: bool,
+    pub(crate) resource: ObjectResource,
+    pub(crate) encryption_key: Option<EncryptionKey>,
 }
 
 impl Object {
-    pub(crate) fn new(
-        client: Client,
-        bucket: impl Into<String>,
-        name: impl Into<String>,
-    ) -> Object {
+    pub(crate) fn new(client: Client, resource: ObjectResource, pinned: bool) -> Object {
         Object {
             client,
-            name: name.into(),
-            bucket: bucket.into(),
+            name: resource.name.clone(),
+            bucket: resource.bucket.clone(),
+            pinned,
+            resource,
+            encryption_key: None,
         }
     }
 
+    /// Use the given customer-supplied key to read this object (and to encrypt its copies).
+    pub fn with_encryption_key(mut self, key: EncryptionKey) -> Object {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    pub(crate) fn uri(&self) -> String {
+        format!(
+            "{}/b/{}/o/{}",
+            self.client.endpoint(),
+            utf8_percent_encode(&self.bucket, NON_ALPHANUMERIC),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        )
+    }
+
+    /// Get the query parameters targeting this object's generation, with the given conditions.
+    pub(crate) fn query(&self, conditions: &Conditions) -> Vec<(&'static str, String)> {
+        let mut query = conditions.query();
+        if self.pinned {
+            query.push(("generation", self.generation().to_string()));
+        }
+        query
+    }
+
+    /// Get the URI of an operation from this object to another one (like `copyTo` or `rewriteTo`).
+    fn operation_uri(&self, operation: &str, bucket: &str, name: &str) -> String {
+        format!(
+            "{}/{}/b/{}/o/{}",
+            self.uri(),
+            operation,
+            utf8_percent_encode(bucket, NON_ALPHANUMERIC),
+            utf8_percent_encode(name, NON_ALPHANUMERIC),
+        )
+    }
+
+    /// Get the query parameters of an operation from this object to another one.
+    fn operation_query(&self, conditions: &Conditions) -> Vec<(&'static str, String)> {
+        let mut query = conditions.query();
+        if self.pinned {
+            query.push(("sourceGeneration", self.generation().to_string()));
+        }
+        query
+    }
+
     /// Get the object's name.
     pub fn name(&self) -> &str {
         self.name.as_str()
@@ -33,57 +304,313 @@ impl Object {
         self.bucket.as_str()
     }
 
-    // /// Insert a new object into the bucket.
-    // pub async fn writer(&mut self, object: Object) -> Result<(), Error> {
-    //     Ok(())
-    // }
+    /// Get the object's generation.
+    pub fn generation(&self) -> i64 {
+        self.resource.generation.parse().unwrap_or_default()
+    }
+
+    /// Get the object's metageneration.
+    pub fn metageneration(&self) -> i64 {
+        self.resource.metageneration.parse().unwrap_or_default()
+    }
 
-    // /// Get an object stored in the bucket.
-    // pub async fn reader(&mut self) -> Result<ObjectReader, Error> {
-    //     Ok(())
-    // }
+    /// Is the handle pinned to its generation ?
+    ///
+    /// Unpinned handles always operate on the live version of the object.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// Get the object's size, in bytes.
+    pub fn size(&self) -> u64 {
+        self.resource.size.parse().unwrap_or_default()
+    }
+
+    /// Get the object's content type.
+    pub fn content_type(&self) -> &str {
+        self.resource.content_type.as_str()
+    }
+
+    /// Get the object's storage class.
+    pub fn storage_class(&self) -> &str {
+        self.resource.storage_class.as_str()
+    }
+
+    /// Get the object's base64-encoded MD5 hash (composite objects have none).
+    pub fn md5_hash(&self) -> Option<&str> {
+        self.resource.md5_hash.as_deref()
+    }
+
+    /// Get the object's base64-encoded CRC32C checksum.
+    pub fn crc32c(&self) -> &str {
+        self.resource.crc32c.as_str()
+    }
+
+    /// Get the base64-encoded SHA-256 hash of the customer-supplied key encrypting the object, if any.
+    pub fn encryption_key_sha256(&self) -> Option<&str> {
+        let encryption = self.resource.customer_encryption.as_ref();
+        encryption.map(|encryption| encryption.key_sha256.as_str())
+    }
+
+    /// Get the object's custom metadata.
+    pub fn metadata(&self) -> Option<&HashMap<String, String>> {
+        self.resource.metadata.as_ref()
+    }
 
     /// Get the entire contents of the object.
+    ///
+    /// The data is checked against the checksums reported by Cloud Storage
+    /// (unless it was decompressed on the fly, for gzip-encoded objects).
     pub async fn get(&mut self) -> Result<Vec<u8>, Error> {
+        self.get_with_conditions(Conditions::default()).await
+    }
+
+    /// Get the entire contents of the object, if the given conditions are met.
+    pub async fn get_with_conditions(&mut self, conditions: Conditions) -> Result<Vec<u8>, Error> {
+        let uri = self.uri();
+        let query = self.query(&conditions);
         let client = &mut self.client;
         let inner = &client.client;
-        let uri = format!(
-            "{}/b/{}/o/{}",
-            Client::ENDPOINT,
-            utf8_percent_encode(&self.bucket, NON_ALPHANUMERIC),
-            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
-        );
 
         let token = client.token_manager.lock().await.token().await?;
-        let request = inner
+        let mut request = inner
             .get(uri.as_str())
             .query(&[("alt", "media")])
+            .query(&query)
+            .header("authorization", token);
+        if let Some(key) = self.encryption_key.as_ref() {
+            key.check(&self.resource)?;
+            request = key.apply(request);
+        }
+        let response = Client::check_status(request.send().await?)?;
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await?.to_vec();
+        if !checksum::is_transcoded(&headers) {
+            Checksums::from_headers(&headers).verify(&bytes)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Get a byte range of the object's contents.
+    ///
+    /// Partial contents cannot be checked against the object's checksums.
+    pub async fn get_range(&mut self, range: Range<u64>) -> Result<Vec<u8>, Error> {
+        if range.start >= range.end {
+            return Ok(Vec::new());
+        }
+        let uri = self.uri();
+        let query = self.query(&Conditions::default());
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .get(uri.as_str())
+            .query(&[("alt", "media")])
+            .query(&query)
             .header("authorization", token)
+            .header("range", format!("bytes={}-{}", range.start, range.end - 1));
+        if let Some(key) = self.encryption_key.as_ref() {
+            key.check(&self.resource)?;
+            request = key.apply(request);
+        }
+        let response = Client::check_status(request.send().await?)?;
+        let bytes = response.bytes().await?.to_vec();
+
+        Ok(bytes)
+    }
+
+    /// Update the object's metadata.
+    pub async fn patch(&mut self, config: ObjectConfig) -> Result<(), Error> {
+        self.patch_with_conditions(config, Conditions::default())
+            .await
+    }
+
+    /// Update the object's metadata, if the given conditions are met.
+    pub async fn patch_with_conditions(
+        &mut self,
+        config: ObjectConfig,
+        conditions: Conditions,
+    ) -> Result<(), Error> {
+        let uri = self.uri();
+        let query = self.query(&conditions);
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let body = ObjectPatchResource::from(config);
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .patch(uri.as_str())
+            .query(&query)
+            .header("authorization", token)
+            .json(&body)
             .send();
         let response = request.await?;
-        let bytes = response.error_for_status()?.bytes().await?.to_vec();
+        self.resource = Client::check_status(response)?.json().await?;
 
-        Ok(bytes)
+        Ok(())
+    }
+
+    /// Copy the object to another location (within the same location and storage class).
+    ///
+    /// If the handle has a customer-supplied key, the copy is encrypted with the same key.
+    /// Returns a handle to the destination object.
+    pub async fn copy_to(&mut self, bucket: &str, name: &str) -> Result<Object, Error> {
+        self.copy_to_with_conditions(bucket, name, Conditions::default())
+            .await
+    }
+
+    /// Copy the object to another location, if the given conditions (on the destination object) are met.
+    ///
+    /// Returns a handle to the destination object.
+    pub async fn copy_to_with_conditions(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
+        let uri = self.operation_uri("copyTo", bucket, name);
+        let query = self.operation_query(&conditions);
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .post(uri.as_str())
+            .query(&query)
+            .header("authorization", token);
+        if let Some(key) = self.encryption_key.as_ref() {
+            key.check(&self.resource)?;
+            request = key.apply(key.apply_source(request));
+        }
+        let response = request.json(&json!({})).send().await?;
+        let resource = Client::check_status(response)?.json().await?;
+
+        let mut object = Object::new(client.clone(), resource, false);
+        object.encryption_key = self.encryption_key.clone();
+        Ok(object)
+    }
+
+    /// Rewrite the object to another location, possibly changing its storage class or encryption key.
+    ///
+    /// Unlike `copy_to`, this works across locations and storage classes, possibly requiring
+    /// multiple calls, which are performed until the rewrite is complete.
+    /// Returns a handle to the destination object.
+    pub async fn rewrite_to(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        config: RewriteConfig,
+    ) -> Result<Object, Error> {
+        self.rewrite_to_with_conditions(bucket, name, config, Conditions::default())
+            .await
+    }
+
+    /// Rewrite the object to another location, if the given conditions (on the destination object) are met.
+    ///
+    /// Returns a handle to the destination object.
+    pub async fn rewrite_to_with_conditions(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        config: RewriteConfig,
+        conditions: Conditions,
+    ) -> Result<Object, Error> {
+        let uri = self.operation_uri("rewriteTo", bucket, name);
+        let mut query = self.operation_query(&conditions);
+        if let Some(kms_key_name) = config.kms_key_name {
+            query.push(("destinationKmsKeyName", kms_key_name));
+        }
+        if let Some(max_bytes) = config.max_bytes_per_call {
+            query.push(("maxBytesRewrittenPerCall", max_bytes.to_string()));
+        }
+        let body = ObjectPatchResource {
+            storage_class: config.storage_class,
+            ..ObjectPatchResource::default()
+        };
+        if let Some(key) = self.encryption_key.as_ref() {
+            key.check(&self.resource)?;
+        }
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let mut rewrite_token = None;
+        loop {
+            let token = client.token_manager.lock().await.token().await?;
+            let mut request = inner
+                .post(uri.as_str())
+                .query(&query)
+                .header("authorization", token);
+            if let Some(rewrite_token) = rewrite_token.as_ref() {
+                request = request.query(&[("rewriteToken", rewrite_token)]);
+            }
+            if let Some(key) = self.encryption_key.as_ref() {
+                request = key.apply_source(request);
+            }
+            if let Some(key) = config.encryption_key.as_ref() {
+                request = key.apply(request);
+            }
+            let response = request.json(&body).send().await?;
+            let response: RewriteResponse = Client::check_status(response)?.json().await?;
+
+            match (response.done, response.resource, response.rewrite_token) {
+                (true, Some(resource), _) => {
+                    let mut object = Object::new(client.clone(), resource, false);
+                    object.encryption_key = config.encryption_key;
+                    break Ok(object);
+                }
+                (true, None, _) => {
+                    break Err(Error::Response(String::from(
+                        "rewrite finished without a resource",
+                    )));
+                }
+                (false, _, Some(token)) => rewrite_token = Some(token),
+                (false, _, None) => {
+                    break Err(Error::Response(String::from(
+                        "rewrite not done but no rewriteToken",
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Generate a V4 signed URL to the object.
+    pub async fn signed_url(&mut self, config: SignedUrlConfig) -> Result<String, Error> {
+        let config = if self.pinned {
+            config.query_param("generation", self.generation().to_string())
+        } else {
+            config
+        };
+        self.client
+            .signed_url(&self.bucket, Some(&self.name), config)
+            .await
     }
 
     /// Delete the object.
+    ///
+    /// If the handle is pinned to a generation, only that generation is deleted.
     pub async fn delete(self) -> Result<(), Error> {
+        self.delete_with_conditions(Conditions::default()).await
+    }
+
+    /// Delete the object, if the given conditions are met.
+    ///
+    /// If the handle is pinned to a generation, only that generation is deleted.
+    pub async fn delete_with_conditions(self, conditions: Conditions) -> Result<(), Error> {
+        let uri = self.uri();
+        let query = self.query(&conditions);
         let client = self.client;
         let inner = client.client;
-        let uri = format!(
-            "{}/b/{}/o/{}",
-            Client::ENDPOINT,
-            utf8_percent_encode(&self.bucket, NON_ALPHANUMERIC),
-            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
-        );
 
         let token = client.token_manager.lock().await.token().await?;
         let request = inner
             .delete(uri.as_str())
+            .query(&query)
             .header("authorization", token)
             .send();
         let response = request.await?;
-        response.error_for_status()?;
+        Client::check_status(response)?;
 
         Ok(())
     }
@@ -0,0 +1,243 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use json::json;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::api::bucket::BucketResource;
+use crate::storage::api::object::{ObjectPatchResource, ObjectRetentionResource};
+use crate::storage::{Bucket, Client, Conditions, Error, Object};
+
+/// Represents the mode of an object's retention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RetentionMode {
+    /// The retention can be shortened or removed, by overriding it.
+    Unlocked,
+    /// The retention can only be extended (it can never be shortened or removed).
+    Locked,
+}
+
+/// Represents the retention of an object, preventing its deletion or replacement until a given time.
+///
+/// The bucket must have object retention enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectRetention {
+    pub(crate) mode: RetentionMode,
+    pub(crate) retain_until_time: DateTime<Utc>,
+}
+
+impl ObjectRetention {
+    /// Retain the object until the given time, in the given mode.
+    pub fn new(mode: RetentionMode, retain_until_time: DateTime<Utc>) -> ObjectRetention {
+        ObjectRetention {
+            mode,
+            retain_until_time,
+        }
+    }
+
+    /// Get the mode of the retention.
+    pub fn mode(&self) -> RetentionMode {
+        self.mode
+    }
+
+    /// Get the time until which the object is retained.
+    pub fn retain_until_time(&self) -> DateTime<Utc> {
+        self.retain_until_time
+    }
+}
+
+impl From<ObjectRetention> for ObjectRetentionResource {
+    fn from(retention: ObjectRetention) -> ObjectRetentionResource {
+        ObjectRetentionResource {
+            mode: retention.mode,
+            retain_until_time: retention
+                .retain_until_time
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+        }
+    }
+}
+
+impl Object {
+    /// Is the object under a temporary hold (which prevents its deletion or replacement) ?
+    pub fn temporary_hold(&self) -> bool {
+        self.resource.temporary_hold.unwrap_or(false)
+    }
+
+    /// Is the object under an event-based hold (which prevents its deletion or replacement) ?
+    ///
+    /// Once released, the object's retention period (if any) starts over.
+    pub fn event_based_hold(&self) -> bool {
+        self.resource.event_based_hold.unwrap_or(false)
+    }
+
+    /// Get the time until which the object is retained by the bucket's retention policy, if any.
+    pub fn retention_expiration_time(&self) -> Option<DateTime<Utc>> {
+        let time = self.resource.retention_expiration_time.as_ref()?;
+        let time = DateTime::parse_from_rfc3339(time).ok()?;
+        Some(time.with_timezone(&Utc))
+    }
+
+    /// Get the object's own retention, if any.
+    pub fn retention(&self) -> Option<ObjectRetention> {
+        let retention = self.resource.retention.as_ref()?;
+        let time = DateTime::parse_from_rfc3339(&retention.retain_until_time).ok()?;
+        Some(ObjectRetention::new(
+            retention.mode,
+            time.with_timezone(&Utc),
+        ))
+    }
+
+    /// Set (`Some`) or remove (`None`) the object's retention.
+    ///
+    /// Shortening or removing an unlocked retention requires `override_unlocked`,
+    /// while a locked retention can only be extended.
+    pub async fn set_retention(
+        &mut self,
+        retention: Option<ObjectRetention>,
+        override_unlocked: bool,
+    ) -> Result<(), Error> {
+        self.set_retention_with_conditions(retention, override_unlocked, Conditions::default())
+            .await
+    }
+
+    /// Set or remove the object's retention, if the given conditions are met.
+    pub async fn set_retention_with_conditions(
+        &mut self,
+        retention: Option<ObjectRetention>,
+        override_unlocked: bool,
+        conditions: Conditions,
+    ) -> Result<(), Error> {
+        let body = ObjectPatchResource {
+            retention: Some(retention.map(ObjectRetentionResource::from)),
+            ..ObjectPatchResource::default()
+        };
+        let mut query = self.query(&conditions);
+        if override_unlocked {
+            query.push(("overrideUnlockedRetention", String::from("true")));
+        }
+        self.patch_resource(body, query).await
+    }
+
+    /// Place (`true`) or release (`false`) a temporary hold on the object.
+    pub async fn set_temporary_hold(&mut self, hold: bool) -> Result<(), Error> {
+        self.set_temporary_hold_with_conditions(hold, Conditions::default())
+            .await
+    }
+
+    /// Place or release a temporary hold on the object, if the given conditions are met.
+    ///
+    /// Using a metageneration precondition guards against concurrent metadata updates.
+    pub async fn set_temporary_hold_with_conditions(
+        &mut self,
+        hold: bool,
+        conditions: Conditions,
+    ) -> Result<(), Error> {
+        let body = ObjectPatchResource {
+            temporary_hold: Some(hold),
+            ..ObjectPatchResource::default()
+        };
+        let query = self.query(&conditions);
+        self.patch_resource(body, query).await
+    }
+
+    /// Place (`true`) or release (`false`) an event-based hold on the object.
+    pub async fn set_event_based_hold(&mut self, hold: bool) -> Result<(), Error> {
+        self.set_event_based_hold_with_conditions(hold, Conditions::default())
+            .await
+    }
+
+    /// Place or release an event-based hold on the object, if the given conditions are met.
+    ///
+    /// Using a metageneration precondition guards against concurrent metadata updates.
+    pub async fn set_event_based_hold_with_conditions(
+        &mut self,
+        hold: bool,
+        conditions: Conditions,
+    ) -> Result<(), Error> {
+        let body = ObjectPatchResource {
+            event_based_hold: Some(hold),
+            ..ObjectPatchResource::default()
+        };
+        let query = self.query(&conditions);
+        self.patch_resource(body, query).await
+    }
+
+    async fn patch_resource(
+        &mut self,
+        body: ObjectPatchResource,
+        query: Vec<(&'static str, String)>,
+    ) -> Result<(), Error> {
+        let uri = self.uri();
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .patch(uri.as_str())
+            .query(&query)
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        self.resource = Client::check_status(response)?.json().await?;
+
+        Ok(())
+    }
+}
+
+impl Bucket {
+    /// Fetch the bucket's current metageneration, as required to lock its retention policy.
+    pub async fn metageneration(&mut self) -> Result<i64, Error> {
+        let client = &mut self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}",
+            client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        );
+
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .get(uri.as_str())
+            .header("authorization", token)
+            .send();
+        let response = request.await?;
+        let resource = Client::check_status(response)?
+            .json::<BucketResource>()
+            .await?;
+
+        resource.metageneration.parse().map_err(|_| {
+            Error::Response(format!(
+                "invalid bucket metageneration: {}",
+                resource.metageneration
+            ))
+        })
+    }
+
+    /// Permanently lock the bucket's retention policy, if the bucket's current metageneration
+    /// matches the given one.
+    ///
+    /// **This cannot be undone**: a locked retention policy can never be removed or shortened,
+    /// and the bucket cannot be deleted until all of its objects have met their retention period.
+    pub async fn lock_retention_policy(&mut self, metageneration: i64) -> Result<(), Error> {
+        let client = &mut self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}/lockRetentionPolicy",
+            client.endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        );
+
+        let conditions = Conditions::default().if_metageneration_match(metageneration);
+        let token = client.token_manager.lock().await.token().await?;
+        let request = inner
+            .post(uri.as_str())
+            .query(&conditions.query())
+            .header("authorization", token)
+            .json(&json!({}))
+            .send();
+        let response = request.await?;
+        Client::check_status(response)?;
+
+        Ok(())
+    }
+}
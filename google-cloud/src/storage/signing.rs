@@ -0,0 +1,554 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use http::Method;
+use json::json;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+
+use crate::error::AuthError;
+use crate::storage::{Client, Error};
+
+/// Everything but the unreserved characters of RFC 3986 gets percent-encoded.
+const ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+/// Same as `ENCODE_SET`, but keeps the path separators of object names.
+const PATH_ENCODE_SET: &AsciiSet = &ENCODE_SET.remove(b'/');
+
+const SIGNING_ALGORITHM: &str = "GOOG4-RSA-SHA256";
+const MAX_EXPIRATION_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Represents the way buckets are addressed in signed URLs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum UrlStyle {
+    /// `https://storage.googleapis.com/{bucket}/{object}` (the default).
+    #[default]
+    PathStyle,
+    /// `https://{bucket}.storage.googleapis.com/{object}`.
+    VirtualHostedStyle,
+    /// `https://{hostname}/{object}`, for buckets served from a custom domain.
+    BucketBoundHostname(String),
+}
+
+impl UrlStyle {
+    fn host(&self, bucket: &str) -> String {
+        match self {
+            UrlStyle::PathStyle => String::from(Client::DOMAIN_NAME),
+            UrlStyle::VirtualHostedStyle => format!("{}.{}", bucket, Client::DOMAIN_NAME),
+            UrlStyle::BucketBoundHostname(hostname) => hostname.clone(),
+        }
+    }
+
+    fn path(&self, bucket: &str, object: Option<&str>) -> String {
+        let object = object
+            .map(|name| utf8_percent_encode(name, PATH_ENCODE_SET).to_string())
+            .unwrap_or_default();
+        match self {
+            UrlStyle::PathStyle if object.is_empty() => {
+                format!("/{}", utf8_percent_encode(bucket, ENCODE_SET))
+            }
+            UrlStyle::PathStyle => {
+                format!("/{}/{}", utf8_percent_encode(bucket, ENCODE_SET), object)
+            }
+            _ => format!("/{}", object),
+        }
+    }
+}
+
+/// Represents the configuration of a V4 signed URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedUrlConfig {
+    pub(crate) method: Method,
+    pub(crate) expires: Duration,
+    pub(crate) start_time: Option<DateTime<Utc>>,
+    pub(crate) headers: BTreeMap<String, Vec<String>>,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) style: UrlStyle,
+    pub(crate) scheme: String,
+}
+
+impl SignedUrlConfig {
+    /// Set the HTTP method the URL can be used with (`GET` by default).
+    pub fn method(mut self, method: Method) -> SignedUrlConfig {
+        self.method = method;
+        self
+    }
+
+    /// Set how long the URL stays valid (15 minutes by default, 7 days at most).
+    pub fn expires(mut self, duration: Duration) -> SignedUrlConfig {
+        self.expires = duration;
+        self
+    }
+
+    /// Set the time from which the URL becomes valid (the current time by default).
+    pub fn start_time(mut self, start_time: DateTime<Utc>) -> SignedUrlConfig {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Add a header that requests made with the URL must send with the same value.
+    pub fn header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> SignedUrlConfig {
+        let value = value
+            .as_ref()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.headers
+            .entry(name.as_ref().trim().to_ascii_lowercase())
+            .or_default()
+            .push(value);
+        self
+    }
+
+    /// Add a query parameter that requests made with the URL must send with the same value.
+    pub fn query_param(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> SignedUrlConfig {
+        self.query.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the way the bucket is addressed in the URL.
+    pub fn style(mut self, style: UrlStyle) -> SignedUrlConfig {
+        self.style = style;
+        self
+    }
+
+    /// Set the scheme of the URL (`https` by default).
+    pub fn scheme(mut self, scheme: impl Into<String>) -> SignedUrlConfig {
+        self.scheme = scheme.into();
+        self
+    }
+}
+
+impl Default for SignedUrlConfig {
+    fn default() -> SignedUrlConfig {
+        SignedUrlConfig {
+            method: Method::GET,
+            expires: Duration::minutes(15),
+            start_time: None,
+            headers: BTreeMap::new(),
+            query: Vec::new(),
+            style: UrlStyle::default(),
+            scheme: String::from("https"),
+        }
+    }
+}
+
+/// Represents a condition on the form fields of a signed POST policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostPolicyCondition {
+    /// The field must exactly match the given value.
+    Equals(String, String),
+    /// The field must start with the given prefix.
+    StartsWith(String, String),
+    /// The uploaded content's size must be in the given (inclusive) range of bytes.
+    ContentLengthRange(u64, u64),
+}
+
+impl PostPolicyCondition {
+    fn to_json(&self) -> json::Value {
+        match self {
+            PostPolicyCondition::Equals(field, value) => {
+                json!(["eq", format!("${}", field), value])
+            }
+            PostPolicyCondition::StartsWith(field, prefix) => {
+                json!(["starts-with", format!("${}", field), prefix])
+            }
+            PostPolicyCondition::ContentLengthRange(min, max) => {
+                json!(["content-length-range", min, max])
+            }
+        }
+    }
+}
+
+/// Represents the configuration of a V4 signed POST policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostPolicyConfig {
+    pub(crate) expires: Duration,
+    pub(crate) start_time: Option<DateTime<Utc>>,
+    pub(crate) fields: BTreeMap<String, String>,
+    pub(crate) conditions: Vec<PostPolicyCondition>,
+    pub(crate) style: UrlStyle,
+    pub(crate) scheme: String,
+}
+
+impl PostPolicyConfig {
+    /// Set how long the policy stays valid (15 minutes by default, 7 days at most).
+    pub fn expires(mut self, duration: Duration) -> PostPolicyConfig {
+        self.expires = duration;
+        self
+    }
+
+    /// Set the time from which the policy becomes valid (the current time by default).
+    pub fn start_time(mut self, start_time: DateTime<Utc>) -> PostPolicyConfig {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Add a form field (like `content-type` or `x-goog-meta-*`) which the upload must send as-is.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<String>) -> PostPolicyConfig {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add a condition that the upload must satisfy.
+    pub fn condition(mut self, condition: PostPolicyCondition) -> PostPolicyConfig {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Set the way the bucket is addressed in the form's URL.
+    pub fn style(mut self, style: UrlStyle) -> PostPolicyConfig {
+        self.style = style;
+        self
+    }
+
+    /// Set the scheme of the form's URL (`https` by default).
+    pub fn scheme(mut self, scheme: impl Into<String>) -> PostPolicyConfig {
+        self.scheme = scheme.into();
+        self
+    }
+}
+
+impl Default for PostPolicyConfig {
+    fn default() -> PostPolicyConfig {
+        PostPolicyConfig {
+            expires: Duration::minutes(15),
+            start_time: None,
+            fields: BTreeMap::new(),
+            conditions: Vec::new(),
+            style: UrlStyle::default(),
+            scheme: String::from("https"),
+        }
+    }
+}
+
+/// Represents a signed POST policy, to be used in an HTML form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostPolicy {
+    pub(crate) url: String,
+    pub(crate) fields: BTreeMap<String, String>,
+}
+
+impl PostPolicy {
+    /// The URL the form must be posted to.
+    pub fn url(&self) -> &str {
+        self.url.as_str()
+    }
+
+    /// The fields the form must include (before the `file` field).
+    pub fn fields(&self) -> &BTreeMap<String, String> {
+        &self.fields
+    }
+}
+
+/// The intermediate results of signing a URL, before the signature itself is computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UrlSigningRequest {
+    pub(crate) url: String,
+    pub(crate) canonical_request: String,
+    pub(crate) string_to_sign: String,
+}
+
+/// The intermediate results of signing a POST policy, before the signature itself is computed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PolicySigningRequest {
+    pub(crate) url: String,
+    pub(crate) policy: String,
+    pub(crate) fields: BTreeMap<String, String>,
+}
+
+#[allow(clippy::result_large_err)]
+fn check_expiration(expires: Duration) -> Result<(), Error> {
+    let seconds = expires.num_seconds();
+    if seconds <= 0 || seconds > MAX_EXPIRATION_SECONDS {
+        return Err(Error::InvalidArgument(format!(
+            "signed expiration must be between 1 and {} seconds, got {}",
+            MAX_EXPIRATION_SECONDS, seconds,
+        )));
+    }
+    Ok(())
+}
+
+fn credential_scope(time: DateTime<Utc>) -> String {
+    format!("{}/auto/storage/goog4_request", time.format("%Y%m%d"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Computes the canonical request and the string to sign for a V4 signed URL.
+#[allow(clippy::result_large_err)]
+pub(crate) fn url_signing_request(
+    config: &SignedUrlConfig,
+    bucket: &str,
+    object: Option<&str>,
+    client_email: &str,
+    now: DateTime<Utc>,
+) -> Result<UrlSigningRequest, Error> {
+    check_expiration(config.expires)?;
+
+    let time = config.start_time.unwrap_or(now);
+    let timestamp = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let scope = credential_scope(time);
+    let host = config.style.host(bucket);
+    let path = config.style.path(bucket, object);
+
+    let mut headers = config.headers.clone();
+    headers
+        .entry(String::from("host"))
+        .or_insert_with(|| vec![host.clone()]);
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, values)| format!("{}:{}\n", name, values.join(",")))
+        .collect();
+    let signed_headers = headers.keys().cloned().collect::<Vec<_>>().join(";");
+
+    let mut query: Vec<(String, String)> = vec![
+        ("X-Goog-Algorithm", String::from(SIGNING_ALGORITHM)),
+        ("X-Goog-Credential", format!("{}/{}", client_email, scope)),
+        ("X-Goog-Date", timestamp.clone()),
+        ("X-Goog-Expires", config.expires.num_seconds().to_string()),
+        ("X-Goog-SignedHeaders", signed_headers.clone()),
+    ]
+    .into_iter()
+    .map(|(name, value)| (String::from(name), value))
+    .chain(config.query.iter().cloned())
+    .map(|(name, value)| {
+        (
+            utf8_percent_encode(&name, ENCODE_SET).to_string(),
+            utf8_percent_encode(&value, ENCODE_SET).to_string(),
+        )
+    })
+    .collect();
+    query.sort();
+    let query = query
+        .into_iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let payload = headers
+        .get("x-goog-content-sha256")
+        .map(|values| values.join(","))
+        .unwrap_or_else(|| String::from("UNSIGNED-PAYLOAD"));
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        config.method.as_str(),
+        path,
+        query,
+        canonical_headers,
+        signed_headers,
+        payload,
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        SIGNING_ALGORITHM,
+        timestamp,
+        scope,
+        hex(digest(&SHA256, canonical_request.as_bytes()).as_ref()),
+    );
+    let url = format!("{}://{}{}?{}", config.scheme, host, path, query);
+
+    Ok(UrlSigningRequest {
+        url,
+        canonical_request,
+        string_to_sign,
+    })
+}
+
+/// Computes the (base64-encoded) policy document to sign for a V4 signed POST policy.
+#[allow(clippy::result_large_err)]
+pub(crate) fn policy_signing_request(
+    config: &PostPolicyConfig,
+    bucket: &str,
+    object: &str,
+    client_email: &str,
+    now: DateTime<Utc>,
+) -> Result<PolicySigningRequest, Error> {
+    check_expiration(config.expires)?;
+
+    let time = config.start_time.unwrap_or(now);
+    let timestamp = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let credential = format!("{}/{}", client_email, credential_scope(time));
+    let expiration = (time + config.expires).format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut fields = config.fields.clone();
+    fields.insert(String::from("key"), String::from(object));
+    fields.insert(String::from("x-goog-date"), timestamp);
+    fields.insert(String::from("x-goog-credential"), credential);
+    fields.insert(
+        String::from("x-goog-algorithm"),
+        String::from(SIGNING_ALGORITHM),
+    );
+
+    let conditions: Vec<json::Value> = config
+        .conditions
+        .iter()
+        .map(PostPolicyCondition::to_json)
+        .chain(Some(json!({ "bucket": bucket })))
+        .chain(
+            fields
+                .iter()
+                .filter(|(name, _)| !name.starts_with("x-ignore-"))
+                .map(|(name, value)| json!({ name.as_str(): value })),
+        )
+        .collect();
+    let document = json!({
+        "conditions": conditions,
+        "expiration": expiration.to_string(),
+    });
+
+    //? The policy document is expected to only contain ASCII characters.
+    let document = json::to_string(&document)?
+        .encode_utf16()
+        .map(|unit| match unit {
+            0..=0x7f => (unit as u8 as char).to_string(),
+            _ => format!("\\u{:04x}", unit),
+        })
+        .collect::<String>();
+    let policy = base64::encode(document);
+
+    let host = config.style.host(bucket);
+    let path = config.style.path(bucket, None);
+    let url = format!(
+        "{}://{}{}/",
+        config.scheme,
+        host,
+        path.trim_end_matches('/')
+    );
+
+    Ok(PolicySigningRequest {
+        url,
+        policy,
+        fields,
+    })
+}
+
+/// Signs a message using RSA-SHA256 with a PEM-encoded (PKCS#8) private key.
+pub(crate) fn sign_with_private_key(
+    private_key: &str,
+    message: &[u8],
+) -> Result<Vec<u8>, AuthError> {
+    let body: String = private_key
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = base64::decode(body.trim()).map_err(|err| AuthError::Signing(err.to_string()))?;
+    let key_pair =
+        RsaKeyPair::from_pkcs8(&der).map_err(|err| AuthError::Signing(err.to_string()))?;
+
+    let mut signature = vec![0; key_pair.public_modulus_len()];
+    key_pair
+        .sign(
+            &RSA_PKCS1_SHA256,
+            &SystemRandom::new(),
+            message,
+            &mut signature,
+        )
+        .map_err(|_| AuthError::Signing(String::from("could not sign message")))?;
+
+    Ok(signature)
+}
+
+impl Client {
+    /// Get the email of the service account which signs URLs and policies.
+    pub(crate) async fn signing_email(&self) -> Result<String, Error> {
+        let token_manager = self.token_manager.lock().await;
+        let creds = token_manager
+            .credentials()
+            .ok_or(AuthError::MissingCredentials)?;
+        Ok(creds.client_email.clone())
+    }
+
+    /// Signs a message using the service account's private key.
+    ///
+    /// If the credentials hold no private key, the message is signed by the IAM `signBlob` API instead.
+    pub(crate) async fn sign(&mut self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let (client_email, private_key) = {
+            let token_manager = self.token_manager.lock().await;
+            let creds = token_manager
+                .credentials()
+                .ok_or(AuthError::MissingCredentials)?;
+            (creds.client_email.clone(), creds.private_key.clone())
+        };
+
+        if !private_key.trim().is_empty() {
+            return Ok(sign_with_private_key(&private_key, message)?);
+        }
+
+        let uri = format!(
+            "{}/projects/-/serviceAccounts/{}:signBlob",
+            Client::IAM_CREDENTIALS_ENDPOINT,
+            utf8_percent_encode(&client_email, ENCODE_SET),
+        );
+        let body = json!({
+            "payload": base64::encode(message),
+        });
+        let token = self.token_manager.lock().await.token().await?;
+        let request = self
+            .client
+            .post(uri.as_str())
+            .header("authorization", token)
+            .json(&body)
+            .send();
+        let response = request.await?;
+        let response: json::Value = Client::check_status(response)?.json().await?;
+        let signature = response["signedBlob"]
+            .as_str()
+            .ok_or_else(|| Error::Response(String::from("signBlob response has no signedBlob")))?;
+        let signature =
+            base64::decode(signature).map_err(|err| AuthError::Signing(err.to_string()))?;
+
+        Ok(signature)
+    }
+
+    /// Signs a URL to the given bucket (and object), valid for the given configuration.
+    pub(crate) async fn signed_url(
+        &mut self,
+        bucket: &str,
+        object: Option<&str>,
+        config: SignedUrlConfig,
+    ) -> Result<String, Error> {
+        let client_email = self.signing_email().await?;
+        let request = url_signing_request(&config, bucket, object, &client_email, Utc::now())?;
+        let signature = self.sign(request.string_to_sign.as_bytes()).await?;
+
+        Ok(format!(
+            "{}&X-Goog-Signature={}",
+            request.url,
+            hex(&signature)
+        ))
+    }
+
+    /// Signs a POST policy for uploading the given object, valid for the given configuration.
+    pub(crate) async fn signed_post_policy(
+        &mut self,
+        bucket: &str,
+        object: &str,
+        config: PostPolicyConfig,
+    ) -> Result<PostPolicy, Error> {
+        let client_email = self.signing_email().await?;
+        let request = policy_signing_request(&config, bucket, object, &client_email, Utc::now())?;
+        let signature = self.sign(request.policy.as_bytes()).await?;
+
+        let mut fields = request.fields;
+        fields.insert(String::from("x-goog-signature"), hex(&signature));
+        fields.insert(String::from("policy"), request.policy);
+
+        Ok(PostPolicy {
+            url: request.url,
+            fields,
+        })
+    }
+}
@@ -0,0 +1,413 @@
+use futures::channel::mpsc;
+use futures::SinkExt;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::StatusCode;
+use tokio::task::JoinHandle;
+use tonic::codec::Streaming;
+
+use crate::storage::api::bytestream::{
+    QueryWriteStatusRequest, ReadRequest, ReadResponse, WriteRequest, WriteResponse,
+};
+use crate::storage::api::object::{ObjectInsertResource, ObjectResource};
+use crate::storage::checksum::{self, Checksums, Hasher};
+use crate::storage::grpc::{object_resource_name, upload_resource_name};
+use crate::storage::{Bucket, Client, Conditions, Error, Object, UploadConfig};
+
+/// Size of the chunks sent to resumable upload sessions (a multiple of 256 KiB, as required).
+const JSON_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+/// Size of the messages sent to the ByteStream service (under the default 4 MiB limit of gRPC).
+const GRPC_CHUNK_SIZE: usize = 2 * 1024 * 1024;
+
+enum Source {
+    Json(reqwest::Response),
+    Grpc(Streaming<ReadResponse>),
+}
+
+/// Represents a stream over the contents of an object.
+pub struct ObjectReader {
+    source: Source,
+    hasher: Option<Hasher>,
+    expected: Checksums,
+}
+
+impl ObjectReader {
+    /// Get the next chunk of the object's contents, or `None` once everything has been read.
+    ///
+    /// Reaching the end checks the data against the object's CRC32C checksum.
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        loop {
+            let chunk = match &mut self.source {
+                Source::Json(response) => response.chunk().await?.map(|bytes| bytes.to_vec()),
+                Source::Grpc(stream) => stream.message().await?.map(|response| response.data),
+            };
+            match chunk {
+                // The ByteStream service may send empty messages to keep the stream alive.
+                Some(data) if data.is_empty() => continue,
+                Some(data) => {
+                    if let Some(hasher) = self.hasher.as_mut() {
+                        hasher.update(&data);
+                    }
+                    return Ok(Some(data));
+                }
+                None => {
+                    if let Some(hasher) = self.hasher.take() {
+                        self.expected.check(&hasher.finish())?;
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// Read the rest of the object's contents at once.
+    pub async fn read_to_end(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        while let Some(chunk) = self.chunk().await? {
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+}
+
+impl Object {
+    /// Open a stream over the contents of the object.
+    ///
+    /// The stream reads the generation described by this handle, so that its data can be
+    /// checked against the handle's CRC32C checksum.
+    /// It goes through gRPC if the client uses it, unless the handle has a customer-supplied key.
+    pub async fn reader(&mut self) -> Result<ObjectReader, Error> {
+        let expected = Checksums {
+            crc32c: Some(String::from(self.crc32c())),
+            md5: None,
+        };
+        let hasher = Some(Hasher::new(true, false));
+
+        if let (Some(service), None) = (self.client.byte_stream.clone(), &self.encryption_key) {
+            let mut service = service;
+            let message = ReadRequest {
+                resource_name: object_resource_name(&self.bucket, &self.name, self.generation()),
+                read_offset: 0,
+                read_limit: 0,
+            };
+            let request = self.client.grpc_request(message).await?;
+            let stream = service.read(request).await?.into_inner();
+            return Ok(ObjectReader {
+                source: Source::Grpc(stream),
+                hasher,
+                expected,
+            });
+        }
+
+        let uri = self.uri();
+        let client = &mut self.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .get(uri.as_str())
+            .query(&[("alt", "media")])
+            .query(&[("generation", self.resource.generation.as_str())])
+            .header("authorization", token);
+        if let Some(key) = self.encryption_key.as_ref() {
+            key.check(&self.resource)?;
+            request = key.apply(request);
+        }
+        let response = Client::check_status(request.send().await?)?;
+        let transcoded = checksum::is_transcoded(response.headers());
+
+        Ok(ObjectReader {
+            source: Source::Json(response),
+            hasher: if transcoded { None } else { hasher },
+            expected,
+        })
+    }
+}
+
+enum Sink {
+    Json,
+    Grpc {
+        sender: mpsc::Sender<WriteRequest>,
+        call: JoinHandle<Result<tonic::Response<WriteResponse>, tonic::Status>>,
+    },
+}
+
+/// Represents a new object being written, as a stream of chunks (through a resumable upload).
+///
+/// Nothing is created until the writer is finished.
+pub struct ObjectWriter {
+    bucket: Bucket,
+    session: String,
+    upload_id: String,
+    sink: Sink,
+    buffer: Vec<u8>,
+    offset: u64,
+    hasher: Hasher,
+}
+
+impl ObjectWriter {
+    /// Get the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.offset + self.buffer.len() as u64
+    }
+
+    /// Write some data to the object.
+    ///
+    /// The data is buffered, and sent once enough of it accumulated.
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.hasher.update(data);
+        self.buffer.extend_from_slice(data);
+        match self.sink {
+            Sink::Json => {
+                while self.buffer.len() >= JSON_CHUNK_SIZE {
+                    let chunk: Vec<u8> = self.buffer.drain(..JSON_CHUNK_SIZE).collect();
+                    self.put_chunk(chunk).await?;
+                }
+            }
+            Sink::Grpc { .. } => {
+                while self.buffer.len() >= GRPC_CHUNK_SIZE {
+                    let chunk: Vec<u8> = self.buffer.drain(..GRPC_CHUNK_SIZE).collect();
+                    self.send_message(chunk, false).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the number of bytes Cloud Storage has persisted so far.
+    pub async fn committed_size(&mut self) -> Result<u64, Error> {
+        if let (Sink::Grpc { .. }, Some(service)) = (&self.sink, &self.bucket.client.byte_stream) {
+            let mut service = service.clone();
+            let message = QueryWriteStatusRequest {
+                resource_name: upload_resource_name(&self.upload_id),
+            };
+            let request = self.bucket.client.grpc_request(message).await?;
+            let response = service.query_write_status(request).await?.into_inner();
+            return Ok(response.committed_size as u64);
+        }
+
+        let response = self.put(Vec::new(), String::from("bytes */*")).await?;
+        Ok(committed_range(&response))
+    }
+
+    /// Send the remaining data and create the object.
+    ///
+    /// The object's checksums (as enabled in the upload configuration) are then checked
+    /// against the written data: on a mismatch, the object is left as is and an error is returned.
+    pub async fn finish(mut self) -> Result<Object, Error> {
+        let total = self.bytes_written();
+        let data = std::mem::take(&mut self.buffer);
+        let object = match self.sink {
+            Sink::Json => {
+                let range = if data.is_empty() {
+                    format!("bytes */{}", total)
+                } else {
+                    format!("bytes {}-{}/{}", self.offset, total - 1, total)
+                };
+                let response = self.put(data, range).await?;
+                let resource = Client::check_status(response)?
+                    .json::<ObjectResource>()
+                    .await?;
+                self.bucket.object_handle(resource, false)
+            }
+            Sink::Grpc { .. } => {
+                self.send_message(data, true).await?;
+                let call = match std::mem::replace(&mut self.sink, Sink::Json) {
+                    Sink::Grpc { sender, call } => {
+                        drop(sender);
+                        call
+                    }
+                    Sink::Json => unreachable!(),
+                };
+                let response = join(call).await?;
+                if response.committed_size as u64 != total {
+                    return Err(Error::Response(format!(
+                        "expected {} bytes to be committed, got {}",
+                        total, response.committed_size
+                    )));
+                }
+                // The ByteStream response does not describe the object: the finished session
+                // does, which pins the generation written here (and not a concurrent one).
+                let response = self.put(Vec::new(), format!("bytes */{}", total)).await?;
+                let resource = Client::check_status(response)?
+                    .json::<ObjectResource>()
+                    .await?;
+                self.bucket.object_handle(resource, false)
+            }
+        };
+
+        let expected = Checksums {
+            crc32c: Some(String::from(object.crc32c())),
+            md5: object.md5_hash().map(String::from),
+        };
+        expected.check(&self.hasher.finish())?;
+
+        Ok(object)
+    }
+
+    async fn put(&mut self, data: Vec<u8>, range: String) -> Result<reqwest::Response, Error> {
+        let client = &self.bucket.client;
+        let inner = &client.client;
+
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .put(self.session.as_str())
+            .header("authorization", token)
+            .header("content-range", range)
+            .header("content-length", data.len());
+        if let Some(key) = self.bucket.encryption_key.as_ref() {
+            request = key.apply(request);
+        }
+        let response = request.body(data).send().await?;
+        match response.status() {
+            StatusCode::PERMANENT_REDIRECT => Ok(response),
+            _ => Client::check_status(response),
+        }
+    }
+
+    /// Send a chunk to the upload session, keeping back the part it did not persist.
+    async fn put_chunk(&mut self, chunk: Vec<u8>) -> Result<(), Error> {
+        let end = self.offset + chunk.len() as u64;
+        let range = format!("bytes {}-{}/*", self.offset, end - 1);
+        let response = self.put(chunk.clone(), range).await?;
+        if response.status() != StatusCode::PERMANENT_REDIRECT {
+            return Err(Error::Response(String::from(
+                "upload session completed before the end of the data",
+            )));
+        }
+
+        let committed = committed_range(&response).clamp(self.offset, end);
+        let rest = &chunk[(committed - self.offset) as usize..];
+        self.buffer.splice(0..0, rest.iter().copied());
+        self.offset = committed;
+        Ok(())
+    }
+
+    async fn send_message(&mut self, data: Vec<u8>, finish_write: bool) -> Result<(), Error> {
+        let len = data.len() as u64;
+        let message = WriteRequest {
+            resource_name: upload_resource_name(&self.upload_id),
+            write_offset: self.offset as i64,
+            finish_write,
+            data,
+        };
+        if let Sink::Grpc { sender, .. } = &mut self.sink {
+            if sender.send(message).await.is_err() {
+                // The call ended early: its outcome tells why.
+                if let Sink::Grpc { call, .. } = std::mem::replace(&mut self.sink, Sink::Json) {
+                    join(call).await?;
+                }
+                return Err(Error::Response(String::from(
+                    "the ByteStream write ended before the end of the data",
+                )));
+            }
+        }
+        self.offset += len;
+        Ok(())
+    }
+}
+
+/// Get the number of bytes persisted by an upload session, from the `Range` header of its response.
+fn committed_range(response: &reqwest::Response) -> u64 {
+    response
+        .headers()
+        .get("range")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("bytes=0-"))
+        .and_then(|end| end.parse::<u64>().ok())
+        .map(|end| end + 1)
+        .unwrap_or(0)
+}
+
+async fn join(
+    call: JoinHandle<Result<tonic::Response<WriteResponse>, tonic::Status>>,
+) -> Result<WriteResponse, Error> {
+    let response = call
+        .await
+        .map_err(|err| Error::IO(std::io::Error::other(err)))??;
+    Ok(response.into_inner())
+}
+
+impl Bucket {
+    /// Start writing a new object into the bucket, as a stream of chunks.
+    ///
+    /// The data goes through gRPC if the client uses it, unless the handle has a customer-supplied key.
+    pub async fn writer(
+        &mut self,
+        name: &str,
+        config: UploadConfig,
+    ) -> Result<ObjectWriter, Error> {
+        self.writer_with_conditions(name, config, Conditions::default())
+            .await
+    }
+
+    /// Start writing a new object into the bucket, if the given conditions are met.
+    ///
+    /// The conditions are checked when the object is created, once the writer is finished.
+    pub async fn writer_with_conditions(
+        &mut self,
+        name: &str,
+        config: UploadConfig,
+        conditions: Conditions,
+    ) -> Result<ObjectWriter, Error> {
+        let client = &self.client;
+        let inner = &client.client;
+        let uri = format!(
+            "{}/b/{}/o",
+            client.upload_endpoint(),
+            utf8_percent_encode(&self.name, NON_ALPHANUMERIC),
+        );
+
+        let hasher = Hasher::new(config.crc32c, config.md5);
+        let metadata = ObjectInsertResource {
+            name: String::from(name),
+            crc32c: None,
+            md5_hash: None,
+            config: config.object.into(),
+        };
+        let token = client.token_manager.lock().await.token().await?;
+        let mut request = inner
+            .post(uri.as_str())
+            .query(&[("uploadType", "resumable")])
+            .query(&conditions.query())
+            .header("authorization", token)
+            .json(&metadata);
+        if let Some(key) = self.encryption_key.as_ref() {
+            request = key.apply(request);
+        }
+        let response = Client::check_status(request.send().await?)?;
+        let session = response
+            .headers()
+            .get("location")
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+            .ok_or_else(|| Error::Response(String::from("missing upload session location")))?;
+        let upload_id = reqwest::Url::parse(&session)
+            .ok()
+            .and_then(|url| {
+                url.query_pairs()
+                    .find(|(key, _)| key == "upload_id")
+                    .map(|(_, value)| value.into_owned())
+            })
+            .unwrap_or_default();
+
+        let sink = match (client.byte_stream.clone(), &self.encryption_key) {
+            (Some(mut service), None) => {
+                let (sender, receiver) = mpsc::channel(1);
+                let request = client.grpc_request(receiver).await?;
+                let call = tokio::spawn(async move { service.write(request).await });
+                Sink::Grpc { sender, call }
+            }
+            _ => Sink::Json,
+        };
+
+        Ok(ObjectWriter {
+            bucket: self.clone(),
+            session,
+            upload_id,
+            sink,
+            buffer: Vec::new(),
+            offset: 0,
+            hasher,
+        })
+    }
+}
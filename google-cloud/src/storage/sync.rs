@@ -0,0 +1,397 @@
+//! Directory synchronization between the local filesystem and a bucket prefix (like `gsutil rsync`).
+//!
+//! Files are compared with objects by size, modification time (stored in the
+//! `goog-reserved-file-mtime` metadata entry, as `gsutil` does) and CRC32C checksum.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use glob::Pattern;
+use tokio::io::AsyncReadExt;
+
+use crate::storage::checksum::Hasher;
+use crate::storage::{Bucket, Error, Object, ObjectConfig, TransferConfig, UploadConfig};
+
+/// The metadata entry holding the modification time of a synchronized file (in seconds since the UNIX epoch).
+pub const MTIME_METADATA_KEY: &str = "goog-reserved-file-mtime";
+
+/// The size of the chunks read from local files, to compute their checksums.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Represents the configuration of a synchronization.
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    pub(crate) concurrency: usize,
+    pub(crate) include: Vec<Pattern>,
+    pub(crate) exclude: Vec<Pattern>,
+    pub(crate) delete_extraneous: bool,
+    pub(crate) dry_run: bool,
+}
+
+impl SyncConfig {
+    /// Set the maximum number of files transferred (or deleted) concurrently (defaults to 8).
+    pub fn concurrency(mut self, concurrency: usize) -> SyncConfig {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Only synchronize files whose relative paths match the given glob pattern (can be called multiple times).
+    #[allow(clippy::result_large_err)]
+    pub fn include(mut self, pattern: &str) -> Result<SyncConfig, Error> {
+        self.include.push(parse_pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Do not synchronize files whose relative paths match the given glob pattern (can be called multiple times).
+    #[allow(clippy::result_large_err)]
+    pub fn exclude(mut self, pattern: &str) -> Result<SyncConfig, Error> {
+        self.exclude.push(parse_pattern(pattern)?);
+        Ok(self)
+    }
+
+    /// Delete files present at the destination but not at the source.
+    pub fn delete_extraneous(mut self, enabled: bool) -> SyncConfig {
+        self.delete_extraneous = enabled;
+        self
+    }
+
+    /// Only report what would be done, without transferring or deleting anything.
+    pub fn dry_run(mut self, enabled: bool) -> SyncConfig {
+        self.dry_run = enabled;
+        self
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|p| p.matches(path));
+        included && !self.exclude.iter().any(|p| p.matches(path))
+    }
+}
+
+impl Default for SyncConfig {
+    fn default() -> SyncConfig {
+        SyncConfig {
+            concurrency: 8,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            delete_extraneous: false,
+            dry_run: false,
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_pattern(pattern: &str) -> Result<Pattern, Error> {
+    Pattern::new(pattern).map_err(|err| {
+        Error::InvalidArgument(format!("invalid glob pattern `{}`: {}", pattern, err))
+    })
+}
+
+/// Represents an action taken (or planned, in dry-run mode) by a synchronization.
+///
+/// Paths are relative to the synchronized directory (and bucket prefix), using `/` as separator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SyncAction {
+    /// A local file is uploaded to the bucket.
+    Upload(String),
+    /// An object is downloaded to the local filesystem.
+    Download(String),
+    /// An extraneous object is deleted from the bucket.
+    DeleteObject(String),
+    /// An extraneous local file is deleted.
+    DeleteFile(String),
+}
+
+/// Represents the outcome of a synchronization.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub(crate) actions: Vec<SyncAction>,
+    pub(crate) unchanged: usize,
+    pub(crate) dry_run: bool,
+}
+
+impl SyncReport {
+    /// Get the actions taken (or planned, in dry-run mode).
+    pub fn actions(&self) -> &[SyncAction] {
+        self.actions.as_slice()
+    }
+
+    /// Get the number of files found identical on both sides.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Was this a dry run (no action actually taken) ?
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+/// A local file, as found while walking the synchronized directory.
+struct LocalFile {
+    path: PathBuf,
+    size: u64,
+    mtime: Option<i64>,
+}
+
+/// Normalize a bucket prefix so that it designates a "directory" (ends with `/`, unless empty).
+fn normalize_prefix(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with('/') {
+        String::from(prefix)
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+/// Get the local path of a relative path (using `/` as separator) within a directory.
+///
+/// Fails on paths which could designate something outside of the directory: absolute ones,
+/// and ones with empty, `.`, `..` or drive prefix components.
+#[allow(clippy::result_large_err)]
+pub(crate) fn local_path(dir: &Path, relative: &str) -> Result<PathBuf, Error> {
+    let mut path = dir.to_path_buf();
+    for part in relative.split('/') {
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) if name == part => path.push(name),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "`{}` is not a safe relative path",
+                    relative
+                )))
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// List the files of a directory tree, keyed by their relative paths (using `/` as separator).
+async fn walk(root: &Path) -> Result<BTreeMap<String, LocalFile>, Error> {
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![(root.to_path_buf(), String::new())];
+    while let Some((dir, relative)) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let relative = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            let metadata = tokio::fs::metadata(entry.path()).await?;
+            if metadata.is_dir() {
+                dirs.push((entry.path(), relative));
+            } else if metadata.is_file() {
+                let mtime = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_secs() as i64);
+                let file = LocalFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    mtime,
+                };
+                files.insert(relative, file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// List the objects under a bucket prefix, keyed by their names relative to the prefix.
+async fn list(bucket: &mut Bucket, prefix: &str) -> Result<BTreeMap<String, Object>, Error> {
+    let objects = bucket.objects_with_prefix(prefix).await?;
+    let objects = objects
+        .into_iter()
+        .filter(|object| !object.name().ends_with('/'))
+        .map(|object| (String::from(&object.name()[prefix.len()..]), object))
+        .collect();
+    Ok(objects)
+}
+
+fn object_mtime(object: &Object) -> Option<i64> {
+    let metadata = object.metadata()?;
+    metadata.get(MTIME_METADATA_KEY)?.parse().ok()
+}
+
+/// Whether a local file and an object have the same contents.
+async fn is_same(file: &LocalFile, object: &Object) -> Result<bool, Error> {
+    if file.size != object.size() {
+        return Ok(false);
+    }
+    if file.mtime.is_some() && file.mtime == object_mtime(object) {
+        return Ok(true);
+    }
+    let mut reader = tokio::fs::File::open(&file.path).await?;
+    let mut hasher = Hasher::new(true, false);
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    let checksums = hasher.finish();
+    Ok(checksums.crc32c.as_deref() == Some(object.crc32c()))
+}
+
+/// Run the given actions with bounded concurrency (unless in dry-run mode), and build the report.
+async fn run<F, Fut>(
+    actions: Vec<SyncAction>,
+    unchanged: usize,
+    config: &SyncConfig,
+    execute: F,
+) -> Result<SyncReport, Error>
+where
+    F: Fn(SyncAction) -> Fut,
+    Fut: std::future::Future<Output = Result<(), Error>>,
+{
+    if !config.dry_run {
+        stream::iter(actions.iter().cloned().map(execute))
+            .buffer_unordered(config.concurrency)
+            .try_collect::<Vec<()>>()
+            .await?;
+    }
+
+    Ok(SyncReport {
+        actions,
+        unchanged,
+        dry_run: config.dry_run,
+    })
+}
+
+/// Synchronize a local directory to a bucket prefix, uploading new and modified files.
+///
+/// With `delete_extraneous`, objects under the prefix without a local counterpart are deleted.
+pub async fn upload_dir(
+    dir: impl AsRef<Path>,
+    bucket: &mut Bucket,
+    prefix: &str,
+    config: SyncConfig,
+) -> Result<SyncReport, Error> {
+    let prefix = normalize_prefix(prefix);
+    let files = walk(dir.as_ref()).await?;
+    let mut objects = list(bucket, &prefix).await?;
+
+    let mut actions = Vec::new();
+    let mut unchanged = 0;
+    for (relative, file) in files.iter() {
+        if !config.matches(relative) {
+            continue;
+        }
+        match objects.remove(relative) {
+            Some(object) if is_same(file, &object).await? => unchanged += 1,
+            _ => actions.push(SyncAction::Upload(relative.clone())),
+        }
+    }
+    if config.delete_extraneous {
+        let extraneous = objects.into_keys();
+        let extraneous = extraneous.filter(|relative| config.matches(relative));
+        actions.extend(extraneous.map(SyncAction::DeleteObject));
+    }
+
+    let bucket = &*bucket;
+    let files = &files;
+    let prefix = prefix.as_str();
+    run(actions, unchanged, &config, |action| async move {
+        let mut bucket = bucket.clone();
+        match action {
+            SyncAction::Upload(relative) => {
+                let file = &files[&relative];
+                let mut object = ObjectConfig::default();
+                if let Some(mtime) = file.mtime {
+                    object = object.metadata(MTIME_METADATA_KEY, mtime.to_string());
+                }
+                let config = UploadConfig::default().object_config(object);
+                let name = format!("{}{}", prefix, relative);
+                let transfer = TransferConfig::default();
+                bucket
+                    .upload_file(&file.path, &name, config, transfer)
+                    .await?;
+            }
+            SyncAction::DeleteObject(relative) => {
+                let name = format!("{}{}", prefix, relative);
+                bucket.object(&name).await?.delete().await?;
+            }
+            SyncAction::Download(_) | SyncAction::DeleteFile(_) => {
+                unreachable!("uploads only plan uploads and object deletions")
+            }
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Synchronize a bucket prefix to a local directory, downloading new and modified objects.
+///
+/// With `delete_extraneous`, local files without a counterpart under the prefix are deleted.
+///
+/// Objects whose names do not map to a path within the directory (like `prefix/../file`)
+/// are skipped.
+pub async fn download_dir(
+    bucket: &mut Bucket,
+    prefix: &str,
+    dir: impl AsRef<Path>,
+    config: SyncConfig,
+) -> Result<SyncReport, Error> {
+    let prefix = normalize_prefix(prefix);
+    let dir = dir.as_ref();
+    tokio::fs::create_dir_all(dir).await?;
+    let mut files = walk(dir).await?;
+    let objects = list(bucket, &prefix).await?;
+
+    let mut actions = Vec::new();
+    let mut unchanged = 0;
+    for (relative, object) in objects.iter() {
+        if !config.matches(relative) || local_path(dir, relative).is_err() {
+            continue;
+        }
+        match files.remove(relative) {
+            Some(file) if is_same(&file, object).await? => unchanged += 1,
+            _ => actions.push(SyncAction::Download(relative.clone())),
+        }
+    }
+    if config.delete_extraneous {
+        let extraneous = files.into_keys();
+        let extraneous = extraneous.filter(|relative| config.matches(relative));
+        actions.extend(extraneous.map(SyncAction::DeleteFile));
+    }
+
+    let objects = &objects;
+    run(actions, unchanged, &config, |action| async move {
+        match action {
+            SyncAction::Download(relative) => {
+                let mut object = objects[&relative].clone();
+                let path = local_path(dir, &relative)?;
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                object
+                    .download_to_file(&path, TransferConfig::default())
+                    .await?;
+                if let Some(mtime) = object_mtime(&object) {
+                    let time = SystemTime::UNIX_EPOCH + Duration::from_secs(mtime.max(0) as u64);
+                    tokio::task::spawn_blocking(move || {
+                        std::fs::File::options()
+                            .write(true)
+                            .open(&path)?
+                            .set_modified(time)
+                    })
+                    .await
+                    .map_err(|err| Error::IO(std::io::Error::other(err)))??;
+                }
+            }
+            SyncAction::DeleteFile(relative) => {
+                tokio::fs::remove_file(local_path(dir, &relative)?).await?;
+            }
+            SyncAction::Upload(_) | SyncAction::DeleteObject(_) => {
+                unreachable!("downloads only plan downloads and file deletions")
+            }
+        }
+        Ok(())
+    })
+    .await
+}
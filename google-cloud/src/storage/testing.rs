@@ -0,0 +1,1277 @@
+//! An in-process fake of the Cloud Storage JSON API, to test code using the storage client offline.
+//!
+//! It covers buckets, objects (media, multipart and resumable uploads, downloads, listing,
+//! patching, copying, composing) and request preconditions, keeping everything in memory.
+//! Object data can also be read and written through its ByteStream API (over gRPC).
+//! Only the live generation of each object is kept.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), google_cloud::error::Error> {
+//! use google_cloud::storage::testing::FakeServer;
+//!
+//! let server = FakeServer::start().await?;
+//! let mut client = server.client("my-project").await?;
+//! let mut bucket = client.create_bucket("my-bucket").await?;
+//! bucket.create_object("hello.txt", "hello", "text/plain").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+// Request handlers return their error responses early, through `Result`.
+#![allow(clippy::result_large_err)]
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use futures::stream;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use hyper::header::HeaderValue;
+use hyper::http::request::Parts;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use json::{json, Value};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::transport::{Channel, NamedService};
+use tonic::Status;
+
+use crate::storage::api::bytestream::byte_stream_server::{ByteStream, ByteStreamServer};
+use crate::storage::api::bytestream::{
+    QueryWriteStatusRequest, QueryWriteStatusResponse, ReadRequest, ReadResponse, WriteRequest,
+    WriteResponse,
+};
+use crate::storage::checksum::Checksums;
+use crate::storage::{Client, Error};
+
+/// The object fields that can be set by clients (at creation or by patching).
+const WRITABLE_FIELDS: [&str; 11] = [
+    "contentType",
+    "contentEncoding",
+    "contentDisposition",
+    "contentLanguage",
+    "cacheControl",
+    "metadata",
+    "storageClass",
+    "temporaryHold",
+    "eventBasedHold",
+    "customTime",
+    "retention",
+];
+
+/// Represents a running fake Cloud Storage server, listening on a local port.
+///
+/// The server shuts down when this handle is dropped.
+pub struct FakeServer {
+    addr: SocketAddr,
+    grpc_addr: SocketAddr,
+    _shutdown: oneshot::Sender<()>,
+    _grpc_shutdown: oneshot::Sender<()>,
+}
+
+impl FakeServer {
+    /// Start a new (empty) server, listening on random local ports (one for the JSON API,
+    /// another one for the ByteStream API).
+    pub async fn start() -> Result<FakeServer, Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let service_state = state.clone();
+        let make_service = make_service_fn(move |_| {
+            let state = service_state.clone();
+            let service = service_fn(move |request| handle(state.clone(), request));
+            async move { Ok::<_, Infallible>(service) }
+        });
+
+        let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))
+            .map_err(|err| Error::IO(io::Error::other(err)))?
+            .serve(make_service);
+        let addr = server.local_addr();
+        state.lock().unwrap().base_url = format!("http://{}", addr);
+
+        let (sender, receiver) = oneshot::channel::<()>();
+        let server = server.with_graceful_shutdown(async {
+            let _ = receiver.await;
+        });
+        tokio::spawn(server);
+
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let grpc_addr = listener.local_addr()?;
+        let incoming = stream::unfold(listener, |listener| async move {
+            let connection = listener.accept().await.map(|(stream, _)| stream);
+            Some((connection, listener))
+        });
+        let (grpc_sender, grpc_receiver) = oneshot::channel::<()>();
+        let grpc_server = tonic::transport::Server::builder()
+            .add_service(ByteStreamServer::new(FakeByteStream { state }))
+            .serve_with_incoming_shutdown(incoming, async {
+                let _ = grpc_receiver.await;
+            });
+        tokio::spawn(grpc_server);
+
+        Ok(FakeServer {
+            addr,
+            grpc_addr,
+            _shutdown: sender,
+            _grpc_shutdown: grpc_sender,
+        })
+    }
+
+    /// Get the server's address.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the server's base URL (usable as `STORAGE_EMULATOR_HOST`).
+    pub fn host(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Get the address of the server's ByteStream API.
+    pub fn grpc_addr(&self) -> SocketAddr {
+        self.grpc_addr
+    }
+
+    /// Create a client for the specified project, connected to this server.
+    pub async fn client(&self, project_name: impl Into<String>) -> Result<Client, Error> {
+        Client::from_emulator_host(project_name, self.host()).await
+    }
+
+    /// Create a client for the specified project, connected to this server,
+    /// and using its ByteStream API for object data.
+    pub async fn grpc_client(&self, project_name: impl Into<String>) -> Result<Client, Error> {
+        let channel = Channel::from_shared(format!("http://{}", self.grpc_addr))?
+            .connect()
+            .await?;
+        let client = self.client(project_name).await?;
+        Ok(client.with_grpc_channel(channel))
+    }
+}
+
+#[derive(Default)]
+struct State {
+    base_url: String,
+    generation: i64,
+    buckets: BTreeMap<String, FakeBucket>,
+    uploads: HashMap<String, PendingUpload>,
+    /// The resources of the objects created by finished upload sessions.
+    finished_uploads: HashMap<String, Value>,
+}
+
+struct FakeBucket {
+    resource: Value,
+    objects: BTreeMap<String, FakeObject>,
+}
+
+#[derive(Clone)]
+struct FakeObject {
+    resource: Value,
+    data: Vec<u8>,
+}
+
+struct PendingUpload {
+    bucket: String,
+    metadata: Value,
+    query: Query,
+    data: Vec<u8>,
+}
+
+/// The decoded query parameters of a request.
+#[derive(Clone, Default)]
+struct Query(HashMap<String, String>);
+
+impl Query {
+    fn parse(query: Option<&str>) -> Query {
+        let params = query
+            .unwrap_or_default()
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (name, value) = param.split_once('=').unwrap_or((param, ""));
+                (decode(name), decode(value))
+            })
+            .collect();
+        Query(params)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    fn get_i64(&self, name: &str) -> Result<Option<i64>, Response<Body>> {
+        match self.get(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| error(StatusCode::BAD_REQUEST, &format!("invalid `{}`", name))),
+            None => Ok(None),
+        }
+    }
+}
+
+fn decode(value: &str) -> String {
+    percent_decode_str(&value.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(value.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        "content-type",
+        HeaderValue::from_static("application/json; charset=UTF-8"),
+    );
+    response
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    let body = json!({
+        "error": {
+            "code": status.as_u16(),
+            "message": message,
+            "errors": [{ "message": message }],
+        },
+    });
+    json_response(status, &body)
+}
+
+fn not_found(what: &str) -> Response<Body> {
+    error(StatusCode::NOT_FOUND, &format!("No such {}", what))
+}
+
+fn parse_body(body: &[u8]) -> Result<Value, Response<Body>> {
+    if body.is_empty() {
+        return Ok(json!({}));
+    }
+    json::from_slice(body).map_err(|err| error(StatusCode::BAD_REQUEST, &err.to_string()))
+}
+
+/// Get the value of a string field of a resource.
+fn field<'a>(resource: &'a Value, name: &str) -> &'a str {
+    resource[name].as_str().unwrap_or_default()
+}
+
+/// Get the value of an integer field of a resource (formatted as a string, as in the API).
+fn int_field(resource: &Value, name: &str) -> i64 {
+    field(resource, name).parse().unwrap_or_default()
+}
+
+/// Get the time until which an object retention (if any) retains its object.
+fn retain_until(retention: &Value) -> Option<DateTime<Utc>> {
+    let time = retention["retainUntilTime"].as_str()?;
+    let time = DateTime::parse_from_rfc3339(time).ok()?;
+    Some(time.with_timezone(&Utc))
+}
+
+/// Only allow extending locked retentions, and shortening unlocked ones when overriding them.
+fn check_retention_change(
+    current: &Value,
+    retention: &Value,
+    query: &Query,
+) -> Result<(), Response<Body>> {
+    let current_time = match retain_until(current) {
+        Some(time) => time,
+        None => return Ok(()),
+    };
+    let shortened = retain_until(retention).is_none_or(|time| time < current_time);
+    if current["mode"] == "Locked" && (shortened || retention["mode"] != "Locked") {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            "Object retention is locked and can only be extended.",
+        ));
+    }
+    if shortened && query.get("overrideUnlockedRetention") != Some("true") {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            "Shortening or removing an unlocked retention requires overrideUnlockedRetention.",
+        ));
+    }
+    Ok(())
+}
+
+/// Merge the writable fields of a patch into a resource (`null` values clear fields).
+fn merge(resource: &mut Value, patch: &Value) {
+    let patch = match patch.as_object() {
+        Some(patch) => patch,
+        None => return,
+    };
+    for (name, value) in patch.iter() {
+        if !WRITABLE_FIELDS.contains(&name.as_str()) {
+            continue;
+        }
+        match (name.as_str(), value) {
+            (_, Value::Null) => {
+                resource.as_object_mut().unwrap().remove(name);
+            }
+            ("metadata", Value::Object(entries)) => {
+                if !resource["metadata"].is_object() {
+                    resource["metadata"] = json!({});
+                }
+                let metadata = resource["metadata"].as_object_mut().unwrap();
+                for (key, value) in entries.iter() {
+                    if value.is_null() {
+                        metadata.remove(key);
+                    } else {
+                        metadata.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            _ => resource[name] = value.clone(),
+        }
+    }
+}
+
+/// Check the generation and metageneration preconditions of a request against a resource.
+fn check_conditions(query: &Query, resource: Option<&Value>) -> Result<(), Response<Body>> {
+    let generation = resource.map(|r| int_field(r, "generation")).unwrap_or(0);
+    let metageneration = resource.map(|r| int_field(r, "metageneration"));
+    let failed = || error(StatusCode::PRECONDITION_FAILED, "Precondition Failed");
+
+    if let Some(expected) = query.get_i64("ifGenerationMatch")? {
+        if generation != expected {
+            return Err(failed());
+        }
+    }
+    if let Some(expected) = query.get_i64("ifGenerationNotMatch")? {
+        if generation == expected {
+            return Err(failed());
+        }
+    }
+    if let Some(expected) = query.get_i64("ifMetagenerationMatch")? {
+        if metageneration != Some(expected) {
+            return Err(failed());
+        }
+    }
+    if let Some(expected) = query.get_i64("ifMetagenerationNotMatch")? {
+        if metageneration == Some(expected) {
+            return Err(failed());
+        }
+    }
+    Ok(())
+}
+
+/// Find a needle in a haystack of bytes.
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if haystack.len() < from + needle.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|idx| idx + from)
+}
+
+/// Split a `multipart/related` body into the headers and contents of its parts.
+fn split_multipart(body: &[u8], boundary: &str) -> Vec<(String, Vec<u8>)> {
+    let delimiter = format!("--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = match find(body, delimiter, 0) {
+        Some(idx) => idx + delimiter.len(),
+        None => return parts,
+    };
+    while let Some(end) = find(body, delimiter, start) {
+        let section = &body[start..end];
+        let section = section.strip_prefix(b"\r\n").unwrap_or(section);
+        let section = section.strip_suffix(b"\r\n").unwrap_or(section);
+        let (headers, content) = match find(section, b"\r\n\r\n", 0) {
+            Some(idx) => (&section[..idx], &section[idx + 4..]),
+            None => (&section[..0], section),
+        };
+        parts.push((
+            String::from_utf8_lossy(headers).into_owned(),
+            content.to_vec(),
+        ));
+        start = end + delimiter.len();
+        if body[start..].starts_with(b"--") {
+            break;
+        }
+    }
+    parts
+}
+
+fn content_type_param<'a>(content_type: &'a str, param: &str) -> Option<&'a str> {
+    content_type
+        .split(';')
+        .filter_map(|part| part.trim().strip_prefix(param)?.strip_prefix('='))
+        .map(|value| value.trim_matches('"'))
+        .next()
+}
+
+fn header<'a>(parts: &'a Parts, name: &str) -> Option<&'a str> {
+    parts
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body.to_vec(),
+        Err(err) => return Ok(error(StatusCode::BAD_REQUEST, &err.to_string())),
+    };
+    let mut state = state.lock().unwrap();
+    let response = state
+        .route(&parts, body)
+        .unwrap_or_else(|response| response);
+    Ok(response)
+}
+
+impl State {
+    fn route(&mut self, parts: &Parts, body: Vec<u8>) -> Result<Response<Body>, Response<Body>> {
+        let query = Query::parse(parts.uri.query());
+        let segments: Vec<String> = parts
+            .uri
+            .path()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(decode)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let method = &parts.method;
+
+        match segments.as_slice() {
+            ["storage", "v1", "b"] if method == Method::GET => Ok(self.list_buckets()),
+            ["storage", "v1", "b"] if method == Method::POST => self.create_bucket(&body),
+            ["storage", "v1", "b", bucket] => match *method {
+                Method::GET => self.get_bucket(bucket),
+                Method::PATCH => self.patch_bucket(bucket, &query, &body),
+                Method::DELETE => self.delete_bucket(bucket, &query),
+                _ => Err(error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed")),
+            },
+            ["storage", "v1", "b", bucket, "o"] if method == Method::GET => {
+                self.list_objects(bucket, &query)
+            }
+            ["storage", "v1", "b", bucket, "o", name] => match *method {
+                Method::GET => self.get_object(parts, bucket, name, &query),
+                Method::PATCH => self.patch_object(bucket, name, &query, &body),
+                Method::DELETE => self.delete_object(bucket, name, &query),
+                _ => Err(error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed")),
+            },
+            ["storage", "v1", "b", bucket, "o", name, "compose"] if method == Method::POST => {
+                self.compose(bucket, name, &query, &body)
+            }
+            ["storage", "v1", "b", source_bucket, "o", source, operation, "b", bucket, "o", name]
+                if method == Method::POST
+                    && (*operation == "copyTo" || *operation == "rewriteTo") =>
+            {
+                if *operation == "rewriteTo" {
+                    if let Some(response) = self.partial_rewrite(source_bucket, source, &query)? {
+                        return Ok(json_response(StatusCode::OK, &response));
+                    }
+                }
+                let object = self.copy(source_bucket, source, bucket, name, &query, &body)?;
+                if *operation == "copyTo" {
+                    return Ok(json_response(StatusCode::OK, &object));
+                }
+                let size = field(&object, "size").to_string();
+                let response = json!({
+                    "kind": "storage#rewriteResponse",
+                    "totalBytesRewritten": size,
+                    "objectSize": size,
+                    "done": true,
+                    "resource": object,
+                });
+                Ok(json_response(StatusCode::OK, &response))
+            }
+            ["storage", "v1", "projects", project, "serviceAccount"] if method == Method::GET => {
+                let email = format!(
+                    "service-{}@gs-project-accounts.iam.gserviceaccount.com",
+                    project
+                );
+                let response = json!({
+                    "kind": "storage#serviceAccount",
+                    "email_address": email,
+                });
+                Ok(json_response(StatusCode::OK, &response))
+            }
+            ["upload", "storage", "v1", "b", bucket, "o"] => match *method {
+                Method::POST => self.upload(parts, bucket, &query, body),
+                Method::PUT => self.resume_upload(parts, &query, body),
+                _ => Err(error(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed")),
+            },
+            _ => Err(error(
+                StatusCode::NOT_IMPLEMENTED,
+                &format!("{} {} is not supported", method, parts.uri.path()),
+            )),
+        }
+    }
+
+    fn next_generation(&mut self) -> i64 {
+        let now = Utc::now();
+        let now = now.timestamp() * 1_000_000 + i64::from(now.timestamp_subsec_micros());
+        self.generation = now.max(self.generation + 1);
+        self.generation
+    }
+
+    fn bucket(&mut self, name: &str) -> Result<&mut FakeBucket, Response<Body>> {
+        self.buckets
+            .get_mut(name)
+            .ok_or_else(|| not_found("bucket"))
+    }
+
+    fn list_buckets(&self) -> Response<Body> {
+        let items: Vec<&Value> = self
+            .buckets
+            .values()
+            .map(|bucket| &bucket.resource)
+            .collect();
+        json_response(
+            StatusCode::OK,
+            &json!({ "kind": "storage#buckets", "items": items }),
+        )
+    }
+
+    fn create_bucket(&mut self, body: &[u8]) -> Result<Response<Body>, Response<Body>> {
+        let config = parse_body(body)?;
+        let name = field(&config, "name").to_string();
+        if name.is_empty() {
+            return Err(error(StatusCode::BAD_REQUEST, "Missing bucket name"));
+        }
+        if self.buckets.contains_key(&name) {
+            return Err(error(StatusCode::CONFLICT, "Bucket already exists"));
+        }
+
+        let time = now();
+        let mut resource = json!({
+            "kind": "storage#bucket",
+            "id": name,
+            "selfLink": format!("{}/storage/v1/b/{}", self.base_url, encode(&name)),
+            "projectNumber": "0",
+            "name": name,
+            "timeCreated": time,
+            "updated": time,
+            "metageneration": "1",
+            "location": "US",
+            "locationType": "multi-region",
+            "storageClass": "STANDARD",
+            "etag": "CAE=",
+        });
+        for (key, value) in config.as_object().into_iter().flatten() {
+            if key != "name" {
+                resource[key] = value.clone();
+            }
+        }
+        let bucket = FakeBucket {
+            resource: resource.clone(),
+            objects: BTreeMap::new(),
+        };
+        self.buckets.insert(name, bucket);
+
+        Ok(json_response(StatusCode::OK, &resource))
+    }
+
+    fn get_bucket(&mut self, name: &str) -> Result<Response<Body>, Response<Body>> {
+        let bucket = self.bucket(name)?;
+        Ok(json_response(StatusCode::OK, &bucket.resource))
+    }
+
+    fn patch_bucket(
+        &mut self,
+        name: &str,
+        query: &Query,
+        body: &[u8],
+    ) -> Result<Response<Body>, Response<Body>> {
+        let patch = parse_body(body)?;
+        let bucket = self.bucket(name)?;
+        check_conditions(query, Some(&bucket.resource))?;
+
+        for (key, value) in patch.as_object().into_iter().flatten() {
+            match value {
+                Value::Null => {
+                    bucket.resource.as_object_mut().unwrap().remove(key);
+                }
+                _ => bucket.resource[key] = value.clone(),
+            }
+        }
+        let metageneration = int_field(&bucket.resource, "metageneration") + 1;
+        bucket.resource["metageneration"] = json!(metageneration.to_string());
+        bucket.resource["updated"] = json!(now());
+
+        Ok(json_response(StatusCode::OK, &bucket.resource))
+    }
+
+    fn delete_bucket(
+        &mut self,
+        name: &str,
+        query: &Query,
+    ) -> Result<Response<Body>, Response<Body>> {
+        let bucket = self.bucket(name)?;
+        check_conditions(query, Some(&bucket.resource))?;
+        if !bucket.objects.is_empty() {
+            return Err(error(
+                StatusCode::CONFLICT,
+                "The bucket you tried to delete is not empty.",
+            ));
+        }
+        self.buckets.remove(name);
+
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap())
+    }
+
+    fn list_objects(
+        &mut self,
+        name: &str,
+        query: &Query,
+    ) -> Result<Response<Body>, Response<Body>> {
+        let bucket = self.bucket(name)?;
+        let prefix = query.get("prefix").unwrap_or_default();
+        let delimiter = query
+            .get("delimiter")
+            .filter(|delimiter| !delimiter.is_empty());
+        let max_results = query.get_i64("maxResults")?.unwrap_or(1000).max(1) as usize;
+        let page_token = query.get("pageToken").unwrap_or_default();
+
+        let mut items = Vec::new();
+        let mut prefixes = Vec::new();
+        let mut next_page_token = None;
+        let names = bucket
+            .objects
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix) && name.as_str() > page_token);
+        for (name, object) in names {
+            if items.len() + prefixes.len() >= max_results {
+                next_page_token =
+                    Some(items.last().map(|item| field(item, "name")).unwrap_or(name));
+                break;
+            }
+            let rest = &name[prefix.len()..];
+            match delimiter
+                .and_then(|delimiter| rest.find(delimiter).map(|idx| idx + delimiter.len()))
+            {
+                Some(end) => {
+                    let common = format!("{}{}", prefix, &rest[..end]);
+                    if !prefixes.contains(&common) {
+                        prefixes.push(common);
+                    }
+                }
+                None => items.push(object.resource.clone()),
+            }
+        }
+
+        let mut response = json!({
+            "kind": "storage#objects",
+            "items": items,
+            "prefixes": prefixes,
+        });
+        if let Some(token) = next_page_token {
+            response["nextPageToken"] = json!(token);
+        }
+        Ok(json_response(StatusCode::OK, &response))
+    }
+
+    /// Find an object, making sure that it matches the requested generation (if any).
+    fn object(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+    ) -> Result<&mut FakeObject, Response<Body>> {
+        let generation = query.get_i64("generation")?;
+        let object = self
+            .bucket(bucket)?
+            .objects
+            .get_mut(name)
+            .ok_or_else(|| not_found("object"))?;
+        match generation {
+            Some(generation) if generation != int_field(&object.resource, "generation") => {
+                Err(not_found("object generation"))
+            }
+            _ => Ok(object),
+        }
+    }
+
+    fn get_object(
+        &mut self,
+        parts: &Parts,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+    ) -> Result<Response<Body>, Response<Body>> {
+        let object = self.object(bucket, name, query)?;
+        check_conditions(query, Some(&object.resource))?;
+        if query.get("alt") != Some("media") {
+            return Ok(json_response(StatusCode::OK, &object.resource));
+        }
+
+        let size = object.data.len() as u64;
+        let range = header(parts, "range")
+            .and_then(|range| range.strip_prefix("bytes="))
+            .and_then(|range| range.split_once('-'))
+            .and_then(|(start, end)| {
+                let start: u64 = start.parse().ok()?;
+                let end: u64 = end.parse().unwrap_or(size.saturating_sub(1));
+                Some((start, end.min(size.saturating_sub(1))))
+            });
+
+        let mut response = Response::builder()
+            .header("content-type", field(&object.resource, "contentType"))
+            .header("x-goog-generation", field(&object.resource, "generation"))
+            .header(
+                "x-goog-metageneration",
+                field(&object.resource, "metageneration"),
+            );
+        let data = match range {
+            Some((start, end)) if start < size && start <= end => {
+                response = response
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header("content-range", format!("bytes {}-{}/{}", start, end, size));
+                object.data[start as usize..=end as usize].to_vec()
+            }
+            Some(_) => {
+                return Err(error(
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    "Requested range not satisfiable",
+                ));
+            }
+            None => {
+                let hash = format!(
+                    "crc32c={},md5={}",
+                    field(&object.resource, "crc32c"),
+                    field(&object.resource, "md5Hash"),
+                );
+                response = response.status(StatusCode::OK).header("x-goog-hash", hash);
+                object.data.clone()
+            }
+        };
+
+        Ok(response.body(Body::from(data)).unwrap())
+    }
+
+    fn patch_object(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+        body: &[u8],
+    ) -> Result<Response<Body>, Response<Body>> {
+        let patch = parse_body(body)?;
+        let object = self.object(bucket, name, query)?;
+        check_conditions(query, Some(&object.resource))?;
+        if let Some(retention) = patch.get("retention") {
+            check_retention_change(&object.resource["retention"], retention, query)?;
+        }
+
+        merge(&mut object.resource, &patch);
+        let metageneration = int_field(&object.resource, "metageneration") + 1;
+        object.resource["metageneration"] = json!(metageneration.to_string());
+        object.resource["updated"] = json!(now());
+
+        Ok(json_response(StatusCode::OK, &object.resource))
+    }
+
+    fn delete_object(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+    ) -> Result<Response<Body>, Response<Body>> {
+        let object = self.object(bucket, name, query)?;
+        check_conditions(query, Some(&object.resource))?;
+        let held = |hold: &str| object.resource[hold].as_bool().unwrap_or(false);
+        if held("temporaryHold") || held("eventBasedHold") {
+            return Err(error(
+                StatusCode::FORBIDDEN,
+                &format!("Object '{}' is under active hold.", name),
+            ));
+        }
+        if retain_until(&object.resource["retention"]).is_some_and(|time| time > Utc::now()) {
+            return Err(error(
+                StatusCode::FORBIDDEN,
+                &format!("Object '{}' is subject to object retention.", name),
+            ));
+        }
+        self.bucket(bucket)?.objects.remove(name);
+
+        Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap())
+    }
+
+    /// Store a new object (as the new live generation), after checking preconditions and checksums.
+    fn insert(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+        metadata: &Value,
+        data: Vec<u8>,
+    ) -> Result<Value, Response<Body>> {
+        if name.is_empty() {
+            return Err(error(StatusCode::BAD_REQUEST, "Missing object name"));
+        }
+        let current = self.bucket(bucket)?.objects.get(name);
+        check_conditions(query, current.map(|object| &object.resource))?;
+
+        let checksums = Checksums::compute(&data, true, true);
+        let crc32c = checksums.crc32c.unwrap_or_default();
+        let md5 = checksums.md5.unwrap_or_default();
+        for (key, computed) in [("crc32c", &crc32c), ("md5Hash", &md5)].iter() {
+            match metadata[*key].as_str() {
+                Some(expected) if expected != computed.as_str() => {
+                    let message = format!(
+                        "Provided {} \"{}\" doesn't match calculated {} \"{}\".",
+                        key, expected, key, computed
+                    );
+                    return Err(error(StatusCode::BAD_REQUEST, &message));
+                }
+                _ => {}
+            }
+        }
+
+        let generation = self.next_generation();
+        let time = now();
+        let mut resource = json!({
+            "kind": "storage#object",
+            "id": format!("{}/{}/{}", bucket, name, generation),
+            "selfLink": format!(
+                "{}/storage/v1/b/{}/o/{}",
+                self.base_url, encode(bucket), encode(name),
+            ),
+            "mediaLink": format!(
+                "{}/download/storage/v1/b/{}/o/{}?generation={}&alt=media",
+                self.base_url, encode(bucket), encode(name), generation,
+            ),
+            "name": name,
+            "bucket": bucket,
+            "generation": generation.to_string(),
+            "metageneration": "1",
+            "contentType": "application/octet-stream",
+            "timeCreated": time,
+            "updated": time,
+            "storageClass": "STANDARD",
+            "timeStorageClassUpdated": time,
+            "size": data.len().to_string(),
+            "md5Hash": md5,
+            "crc32c": crc32c,
+            "etag": base64::encode(generation.to_be_bytes()),
+        });
+        merge(&mut resource, metadata);
+
+        let object = FakeObject {
+            resource: resource.clone(),
+            data,
+        };
+        self.bucket(bucket)?
+            .objects
+            .insert(String::from(name), object);
+
+        Ok(resource)
+    }
+
+    fn upload(
+        &mut self,
+        parts: &Parts,
+        bucket: &str,
+        query: &Query,
+        body: Vec<u8>,
+    ) -> Result<Response<Body>, Response<Body>> {
+        self.bucket(bucket)?;
+        let content_type = header(parts, "content-type").unwrap_or("application/octet-stream");
+
+        match query.get("uploadType") {
+            Some("media") => {
+                let name = query.get("name").unwrap_or_default();
+                let metadata = json!({ "contentType": content_type });
+                let resource = self.insert(bucket, name, query, &metadata, body)?;
+                Ok(json_response(StatusCode::OK, &resource))
+            }
+            Some("multipart") => {
+                let boundary = content_type_param(content_type, "boundary")
+                    .ok_or_else(|| error(StatusCode::BAD_REQUEST, "Missing multipart boundary"))?;
+                let mut multipart = split_multipart(&body, boundary).into_iter();
+                let (metadata, (data_headers, data)) = match (multipart.next(), multipart.next()) {
+                    (Some((_, metadata)), Some(data)) => (parse_body(&metadata)?, data),
+                    _ => return Err(error(StatusCode::BAD_REQUEST, "Invalid multipart body")),
+                };
+                let mut metadata = metadata;
+                if metadata["contentType"].is_null() {
+                    let data_type = data_headers
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-type"))
+                        .map(|(_, value)| value.trim());
+                    if let Some(data_type) = data_type {
+                        metadata["contentType"] = json!(data_type);
+                    }
+                }
+                let name = query
+                    .get("name")
+                    .map(String::from)
+                    .unwrap_or_else(|| field(&metadata, "name").to_string());
+                let resource = self.insert(bucket, &name, query, &metadata, data)?;
+                Ok(json_response(StatusCode::OK, &resource))
+            }
+            Some("resumable") => {
+                let mut metadata = parse_body(&body)?;
+                if let Some(name) = query.get("name") {
+                    metadata["name"] = json!(name);
+                }
+                if let Some(content_type) = header(parts, "x-upload-content-type") {
+                    if metadata["contentType"].is_null() {
+                        metadata["contentType"] = json!(content_type);
+                    }
+                }
+                let upload_id = format!("upload-{}", self.next_generation());
+                let location = format!(
+                    "{}/upload/storage/v1/b/{}/o?uploadType=resumable&upload_id={}",
+                    self.base_url,
+                    encode(bucket),
+                    upload_id,
+                );
+                let upload = PendingUpload {
+                    bucket: String::from(bucket),
+                    metadata,
+                    query: query.clone(),
+                    data: Vec::new(),
+                };
+                self.uploads.insert(upload_id, upload);
+
+                Ok(Response::builder()
+                    .status(StatusCode::OK)
+                    .header("location", location)
+                    .body(Body::empty())
+                    .unwrap())
+            }
+            _ => Err(error(StatusCode::BAD_REQUEST, "Unsupported upload type")),
+        }
+    }
+
+    fn resume_upload(
+        &mut self,
+        parts: &Parts,
+        query: &Query,
+        body: Vec<u8>,
+    ) -> Result<Response<Body>, Response<Body>> {
+        let upload_id = query.get("upload_id").unwrap_or_default().to_string();
+        if let Some(resource) = self.finished_uploads.get(&upload_id) {
+            return Ok(json_response(StatusCode::OK, resource));
+        }
+        let upload = self
+            .uploads
+            .get_mut(&upload_id)
+            .ok_or_else(|| not_found("upload"))?;
+
+        // `Content-Range: bytes {start}-{end}/{total}` (or `bytes */{total}` to query the status).
+        let content_range =
+            header(parts, "content-range").and_then(|range| range.strip_prefix("bytes "));
+        let total = content_range
+            .and_then(|range| range.rsplit('/').next())
+            .and_then(|total| total.parse::<usize>().ok());
+        upload.data.extend_from_slice(&body);
+
+        let complete = match total {
+            Some(total) => upload.data.len() >= total,
+            None => content_range.is_none(),
+        };
+        if !complete {
+            let mut response = Response::builder().status(308);
+            if !upload.data.is_empty() {
+                response = response.header("range", format!("bytes=0-{}", upload.data.len() - 1));
+            }
+            return Ok(response.body(Body::empty()).unwrap());
+        }
+
+        let resource = self.complete_upload(&upload_id)?;
+        Ok(json_response(StatusCode::OK, &resource))
+    }
+
+    /// Create the object of an upload session, with all of its data.
+    fn complete_upload(&mut self, upload_id: &str) -> Result<Value, Response<Body>> {
+        let upload = self
+            .uploads
+            .remove(upload_id)
+            .ok_or_else(|| not_found("upload"))?;
+        let name = field(&upload.metadata, "name").to_string();
+        let resource = self.insert(
+            &upload.bucket,
+            &name,
+            &upload.query,
+            &upload.metadata,
+            upload.data,
+        )?;
+        self.finished_uploads
+            .insert(String::from(upload_id), resource.clone());
+        Ok(resource)
+    }
+
+    fn copy(
+        &mut self,
+        source_bucket: &str,
+        source: &str,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+        body: &[u8],
+    ) -> Result<Value, Response<Body>> {
+        let patch = parse_body(body)?;
+        let mut source_query = Query::default();
+        if let Some(generation) = query.get("sourceGeneration") {
+            source_query
+                .0
+                .insert(String::from("generation"), String::from(generation));
+        }
+        let object = self.object(source_bucket, source, &source_query)?.clone();
+
+        let mut metadata = json!({});
+        merge(&mut metadata, &object.resource);
+        metadata["temporaryHold"] = Value::Null;
+        metadata["eventBasedHold"] = Value::Null;
+        metadata["retention"] = Value::Null;
+        merge(&mut metadata, &patch);
+        self.insert(bucket, name, query, &metadata, object.data)
+    }
+
+    /// Rewrites at most `maxBytesRewrittenPerCall` bytes per call, tracking the progress in
+    /// the rewrite token (the object is only written by the last call).
+    fn partial_rewrite(
+        &mut self,
+        source_bucket: &str,
+        source: &str,
+        query: &Query,
+    ) -> Result<Option<Value>, Response<Body>> {
+        let max_bytes = match query.get("maxBytesRewrittenPerCall") {
+            Some(max_bytes) => max_bytes.parse::<u64>().unwrap_or(u64::MAX),
+            None => return Ok(None),
+        };
+        let mut source_query = Query::default();
+        if let Some(generation) = query.get("sourceGeneration") {
+            source_query
+                .0
+                .insert(String::from("generation"), String::from(generation));
+        }
+        let size = self
+            .object(source_bucket, source, &source_query)?
+            .data
+            .len() as u64;
+        let rewritten = query
+            .get("rewriteToken")
+            .and_then(|token| token.parse::<u64>().ok())
+            .unwrap_or_default()
+            .saturating_add(max_bytes);
+        if rewritten >= size {
+            return Ok(None);
+        }
+
+        Ok(Some(json!({
+            "kind": "storage#rewriteResponse",
+            "totalBytesRewritten": rewritten.to_string(),
+            "objectSize": size.to_string(),
+            "done": false,
+            "rewriteToken": rewritten.to_string(),
+        })))
+    }
+
+    fn compose(
+        &mut self,
+        bucket: &str,
+        name: &str,
+        query: &Query,
+        body: &[u8],
+    ) -> Result<Response<Body>, Response<Body>> {
+        let request = parse_body(body)?;
+        let sources = request["sourceObjects"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if sources.is_empty() || sources.len() > 32 {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "Between 1 and 32 source objects are required",
+            ));
+        }
+
+        let mut data = Vec::new();
+        for source in sources.iter() {
+            let mut source_query = Query::default();
+            if let Some(generation) = source["generation"].as_str() {
+                source_query
+                    .0
+                    .insert(String::from("generation"), String::from(generation));
+            }
+            let object = self.object(bucket, field(source, "name"), &source_query)?;
+            if let Some(expected) = source["objectPreconditions"]["ifGenerationMatch"].as_str() {
+                if field(&object.resource, "generation") != expected {
+                    return Err(error(
+                        StatusCode::PRECONDITION_FAILED,
+                        "Precondition Failed",
+                    ));
+                }
+            }
+            data.extend_from_slice(&object.data);
+        }
+
+        let mut resource = self.insert(bucket, name, query, &request["destination"], data)?;
+        resource["componentCount"] = json!(sources.len().to_string());
+        resource.as_object_mut().unwrap().remove("md5Hash");
+        if let Some(object) = self.bucket(bucket)?.objects.get_mut(name) {
+            object.resource = resource.clone();
+        }
+
+        Ok(json_response(StatusCode::OK, &resource))
+    }
+}
+
+/// Size of the messages sent by the fake ByteStream service.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Turn an error response of the JSON API into a gRPC status.
+fn to_status(response: Response<Body>) -> Status {
+    let message = response
+        .status()
+        .canonical_reason()
+        .unwrap_or("Unknown error");
+    match response.status() {
+        StatusCode::BAD_REQUEST => Status::invalid_argument(message),
+        StatusCode::FORBIDDEN => Status::permission_denied(message),
+        StatusCode::NOT_FOUND => Status::not_found(message),
+        StatusCode::PRECONDITION_FAILED => Status::failed_precondition(message),
+        _ => Status::unknown(message),
+    }
+}
+
+/// Parse a ByteStream object resource name (`projects/_/buckets/{bucket}/objects/{object}#{generation}`).
+fn parse_object_resource_name(resource_name: &str) -> Option<(String, String, i64)> {
+    let rest = resource_name.strip_prefix("projects/_/buckets/")?;
+    let (bucket, rest) = rest.split_once("/objects/")?;
+    let (name, generation) = rest.rsplit_once('#')?;
+    Some((String::from(bucket), decode(name), generation.parse().ok()?))
+}
+
+/// Parse a ByteStream upload resource name (`uploads/{upload_id}`).
+fn parse_upload_resource_name(resource_name: &str) -> Result<&str, Status> {
+    resource_name.strip_prefix("uploads/").ok_or_else(|| {
+        Status::invalid_argument(format!("invalid resource name: {}", resource_name))
+    })
+}
+
+// The generated server only implements this with the `transport` feature of `tonic-build`.
+impl<T: ByteStream> NamedService for ByteStreamServer<T> {
+    const NAME: &'static str = "google.bytestream.ByteStream";
+}
+
+struct FakeByteStream {
+    state: Arc<Mutex<State>>,
+}
+
+#[tonic::async_trait]
+impl ByteStream for FakeByteStream {
+    type ReadStream = stream::Iter<std::vec::IntoIter<Result<ReadResponse, Status>>>;
+
+    async fn read(
+        &self,
+        request: tonic::Request<ReadRequest>,
+    ) -> Result<tonic::Response<Self::ReadStream>, Status> {
+        let request = request.into_inner();
+        let (bucket, name, generation) = parse_object_resource_name(&request.resource_name)
+            .ok_or_else(|| Status::invalid_argument("invalid resource name"))?;
+        let data = {
+            let mut state = self.state.lock().unwrap();
+            let mut query = Query::default();
+            query
+                .0
+                .insert(String::from("generation"), generation.to_string());
+            state
+                .object(&bucket, &name, &query)
+                .map_err(to_status)?
+                .data
+                .clone()
+        };
+
+        let offset = request.read_offset;
+        if offset < 0 || offset as usize > data.len() || request.read_limit < 0 {
+            return Err(Status::out_of_range("invalid read offset or limit"));
+        }
+        let end = match request.read_limit {
+            0 => data.len(),
+            limit => data.len().min((offset + limit) as usize),
+        };
+        let messages: Vec<_> = data[offset as usize..end]
+            .chunks(READ_CHUNK_SIZE)
+            .map(|chunk| {
+                Ok(ReadResponse {
+                    data: chunk.to_vec(),
+                })
+            })
+            .collect();
+
+        Ok(tonic::Response::new(stream::iter(messages)))
+    }
+
+    async fn write(
+        &self,
+        request: tonic::Request<tonic::Streaming<WriteRequest>>,
+    ) -> Result<tonic::Response<WriteResponse>, Status> {
+        let mut stream = request.into_inner();
+        let mut upload_id = None;
+        let mut committed_size = 0;
+        while let Some(message) = stream.message().await? {
+            let id = match upload_id.as_ref() {
+                Some(id) => String::clone(id),
+                None => String::from(parse_upload_resource_name(&message.resource_name)?),
+            };
+            let mut state = self.state.lock().unwrap();
+            let upload = state
+                .uploads
+                .get_mut(&id)
+                .ok_or_else(|| Status::not_found(format!("no such upload: {}", id)))?;
+            if message.write_offset != upload.data.len() as i64 {
+                return Err(Status::invalid_argument(format!(
+                    "expected write offset {}, got {}",
+                    upload.data.len(),
+                    message.write_offset
+                )));
+            }
+            upload.data.extend_from_slice(&message.data);
+            committed_size = upload.data.len() as i64;
+            if message.finish_write {
+                state.complete_upload(&id).map_err(to_status)?;
+                break;
+            }
+            upload_id = Some(id);
+        }
+
+        Ok(tonic::Response::new(WriteResponse { committed_size }))
+    }
+
+    async fn query_write_status(
+        &self,
+        request: tonic::Request<QueryWriteStatusRequest>,
+    ) -> Result<tonic::Response<QueryWriteStatusResponse>, Status> {
+        let request = request.into_inner();
+        let id = parse_upload_resource_name(&request.resource_name)?;
+        let state = self.state.lock().unwrap();
+        let response = match (state.uploads.get(id), state.finished_uploads.get(id)) {
+            (Some(upload), _) => QueryWriteStatusResponse {
+                committed_size: upload.data.len() as i64,
+                complete: false,
+            },
+            (None, Some(resource)) => QueryWriteStatusResponse {
+                committed_size: int_field(resource, "size"),
+                complete: true,
+            },
+            (None, None) => return Err(Status::not_found(format!("no such upload: {}", id))),
+        };
+
+        Ok(tonic::Response::new(response))
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::storage::{random_token, Bucket, ComposeSource, Error, Object, UploadConfig};
+
+/// Composition accepts at most 32 source objects.
+const MAX_PARTS: u64 = 32;
+
+/// Represents the configuration of a parallel transfer (composite upload or sliced download).
+#[derive(Clone)]
+pub struct TransferConfig {
+    pub(crate) concurrency: usize,
+    pub(crate) part_size: u64,
+    pub(crate) progress: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+}
+
+impl TransferConfig {
+    /// Set the maximum number of parts transferred concurrently (defaults to 4).
+    pub fn concurrency(mut self, concurrency: usize) -> TransferConfig {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the size of each part, in bytes (defaults to 32 MiB).
+    ///
+    /// Uploads are split into at most 32 parts, so the part size may get increased for large files.
+    pub fn part_size(mut self, part_size: u64) -> TransferConfig {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// Call the given function, with the number of bytes transferred so far
+    /// and the total number of bytes, every time a part has been transferred.
+    pub fn on_progress(
+        mut self,
+        progress: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> TransferConfig {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+impl Default for TransferConfig {
+    fn default() -> TransferConfig {
+        TransferConfig {
+            concurrency: 4,
+            part_size: 32 * 1024 * 1024,
+            progress: None,
+        }
+    }
+}
+
+impl fmt::Debug for TransferConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransferConfig")
+            .field("concurrency", &self.concurrency)
+            .field("part_size", &self.part_size)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Keeps track of the number of transferred bytes, reporting it to the progress callback.
+struct Progress {
+    transferred: AtomicU64,
+    total: u64,
+    callback: Option<Arc<dyn Fn(u64, u64) + Send + Sync>>,
+}
+
+impl Progress {
+    fn new(total: u64, config: &TransferConfig) -> Progress {
+        Progress {
+            transferred: AtomicU64::new(0),
+            total,
+            callback: config.progress.clone(),
+        }
+    }
+
+    fn advance(&self, bytes: u64) {
+        let transferred = self.transferred.fetch_add(bytes, Ordering::SeqCst) + bytes;
+        if let Some(callback) = self.callback.as_ref() {
+            callback(transferred, self.total);
+        }
+    }
+}
+
+/// Split `size` bytes into consecutive ranges of `part_size` bytes (the last one may be shorter).
+fn split(size: u64, part_size: u64) -> Vec<Range<u64>> {
+    (0..size)
+        .step_by(part_size as usize)
+        .map(|start| start..(start + part_size).min(size))
+        .collect()
+}
+
+/// Combine the CRC32C checksums of consecutive parts, given along with their lengths.
+fn combine(parts: &[(u32, u64)]) -> String {
+    let crc = parts.iter().fold(0, |crc, (part_crc, len)| {
+        crc32c::crc32c_combine(crc, *part_crc, *len as usize)
+    });
+    base64::encode(crc.to_be_bytes())
+}
+
+/// Check the CRC32C checksum reported for an object against the one computed from its parts.
+#[allow(clippy::result_large_err)]
+fn verify(object: &Object, parts: &[(u32, u64)]) -> Result<(), Error> {
+    let computed = combine(parts);
+    if object.crc32c() == computed {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch {
+            algorithm: String::from("crc32c"),
+            expected: String::from(object.crc32c()),
+            computed,
+        })
+    }
+}
+
+async fn read_range(path: &Path, range: Range<u64>) -> Result<Vec<u8>, Error> {
+    let mut file = File::open(path).await?;
+    file.seek(SeekFrom::Start(range.start)).await?;
+    let mut data = vec![0; (range.end - range.start) as usize];
+    file.read_exact(&mut data).await?;
+    Ok(data)
+}
+
+async fn write_range(path: &Path, offset: u64, data: &[u8]) -> Result<(), Error> {
+    let mut file = OpenOptions::new().write(true).open(path).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.write_all(data).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+impl Bucket {
+    /// Upload a local file into the bucket, as a parallel composite upload.
+    ///
+    /// The file is split into parts, uploaded concurrently as temporary objects,
+    /// which are then composed into the final object and deleted.
+    /// The CRC32C checksum of the final object is checked against the file's contents.
+    /// Composite objects have no MD5 hash, so the `md5` setting of the upload configuration is ignored.
+    pub async fn upload_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        name: &str,
+        config: UploadConfig,
+        transfer: TransferConfig,
+    ) -> Result<Object, Error> {
+        let path = path.as_ref();
+        let size = tokio::fs::metadata(path).await?.len();
+        let part_size = transfer.part_size.max(size.div_ceil(MAX_PARTS));
+        let progress = Progress::new(size, &transfer);
+
+        if size <= part_size {
+            let data = read_range(path, 0..size).await?;
+            let object = self.upload(name, data, config).await?;
+            progress.advance(size);
+            return Ok(object);
+        }
+
+        let upload_id = random_token(8)?;
+
+        let uploads = split(size, part_size)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, range)| {
+                let mut bucket = self.clone();
+                let part_name = format!("{}.upload-{}.part-{:02}", name, upload_id, idx);
+                let progress = &progress;
+                async move {
+                    let data = read_range(path, range.clone()).await?;
+                    let crc = crc32c::crc32c(&data);
+                    let part = bucket
+                        .upload(&part_name, data, UploadConfig::default())
+                        .await?;
+                    progress.advance(range.end - range.start);
+                    Ok::<_, Error>((idx, (crc, range.end - range.start), part))
+                }
+            });
+        let results: Vec<Result<_, Error>> = stream::iter(uploads)
+            .buffer_unordered(transfer.concurrency)
+            .collect()
+            .await;
+
+        let mut parts = Vec::with_capacity(results.len());
+        let mut error = None;
+        for result in results {
+            match result {
+                Ok(part) => parts.push(part),
+                Err(err) => error = error.or(Some(err)),
+            }
+        }
+        parts.sort_by_key(|(idx, _, _)| *idx);
+
+        let composed = match error {
+            Some(err) => Err(err),
+            None => {
+                let sources: Vec<ComposeSource> =
+                    parts.iter().map(|(_, _, part)| part.into()).collect();
+                self.compose(name, sources, config.object).await
+            }
+        };
+
+        for (_, _, part) in parts.iter() {
+            let _ = part.clone().delete().await;
+        }
+
+        let object = composed?;
+        let checksums: Vec<_> = parts.iter().map(|(_, checksum, _)| *checksum).collect();
+        verify(&object, &checksums)?;
+
+        Ok(object)
+    }
+}
+
+impl Object {
+    /// Download the object into a local file, as a sliced download.
+    ///
+    /// The file is preallocated, and byte ranges of the object are fetched concurrently
+    /// and written into place.
+    /// The CRC32C checksum of the object is checked against the downloaded contents.
+    /// Objects stored with gzip content encoding are downloaded at once, as they cannot be sliced.
+    pub async fn download_to_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        transfer: TransferConfig,
+    ) -> Result<(), Error> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let size = self.size();
+        let progress = Progress::new(size, &transfer);
+
+        let gzipped = self.resource.content_encoding.as_deref() == Some("gzip");
+        if gzipped || size <= transfer.part_size {
+            let data = self.get().await?;
+            File::create(&path).await?.write_all(&data).await?;
+            progress.advance(size);
+            return Ok(());
+        }
+
+        File::create(&path).await?.set_len(size).await?;
+
+        let mut pinned = self.clone();
+        pinned.pinned = true;
+        let downloads =
+            split(size, transfer.part_size)
+                .into_iter()
+                .enumerate()
+                .map(|(idx, range)| {
+                    let mut object = pinned.clone();
+                    let path = path.as_path();
+                    let progress = &progress;
+                    async move {
+                        let data = object.get_range(range.clone()).await?;
+                        if data.len() as u64 != range.end - range.start {
+                            let message = format!(
+                                "expected {} bytes at offset {}, got {}",
+                                range.end - range.start,
+                                range.start,
+                                data.len(),
+                            );
+                            return Err(Error::IO(io::Error::new(
+                                io::ErrorKind::UnexpectedEof,
+                                message,
+                            )));
+                        }
+                        let crc = crc32c::crc32c(&data);
+                        write_range(path, range.start, &data).await?;
+                        progress.advance(range.end - range.start);
+                        Ok((idx, (crc, range.end - range.start)))
+                    }
+                });
+        let mut parts: Vec<_> = stream::iter(downloads)
+            .buffer_unordered(transfer.concurrency)
+            .try_collect()
+            .await?;
+        parts.sort_by_key(|(idx, _)| *idx);
+
+        let checksums: Vec<_> = parts.into_iter().map(|(_, checksum)| checksum).collect();
+        verify(self, &checksums)
+    }
+}
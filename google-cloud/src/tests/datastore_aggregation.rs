@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::datastore::api;
+use crate::datastore::api::value::ValueType;
+use crate::datastore::{AggregationQuery, AggregationResult, Error, Query, Value};
+
+#[test]
+fn datastore_aggregation_validation() {
+    let valid = AggregationQuery::new(Query::new("users"))
+        .count("total")
+        .count_up_to("bounded", 100)
+        .sum("total_age", "age")
+        .avg("average_age", "age");
+    assert!(valid.validate().is_ok());
+
+    let invalid = vec![
+        AggregationQuery::new(Query::new("users")),
+        AggregationQuery::new(Query::new("users"))
+            .count("total")
+            .sum("total", "age"),
+        AggregationQuery::new(Query::new("users")).count_up_to("total", -1),
+        (0..=AggregationQuery::MAX_AGGREGATIONS)
+            .fold(AggregationQuery::new(Query::new("users")), |query, idx| {
+                query.count(format!("count_{}", idx))
+            }),
+    ];
+    for query in invalid {
+        assert!(
+            matches!(query.validate(), Err(Error::InvalidArgument(_))),
+            "aggregation should be invalid: {:?}",
+            query,
+        );
+    }
+}
+
+#[test]
+fn datastore_aggregation_result_conversion() {
+    let value = |value_type| api::Value {
+        meaning: 0,
+        exclude_from_indexes: false,
+        value_type: Some(value_type),
+    };
+    let mut properties = HashMap::new();
+    properties.insert(String::from("total"), value(ValueType::IntegerValue(3)));
+    properties.insert(String::from("average"), value(ValueType::DoubleValue(1.5)));
+    properties.insert(String::from("empty"), value(ValueType::NullValue(0)));
+
+    let result = AggregationResult::from(api::AggregationResult {
+        aggregate_properties: properties,
+    });
+    assert_eq!(result.get_i64("total"), Some(3));
+    assert_eq!(result.get_f64("total"), Some(3.0));
+    assert_eq!(result.get_f64("average"), Some(1.5));
+    assert_eq!(result.get_i64("average"), None);
+    assert_eq!(result.get("empty"), None);
+    assert_eq!(result.get("missing"), None);
+    assert_eq!(
+        result.into_values().get("total"),
+        Some(&Value::IntegerValue(3))
+    );
+}
@@ -0,0 +1,47 @@
+use crate::datastore::api::gql_query_parameter::ParameterType;
+use crate::datastore::{Error, GqlQuery};
+
+#[test]
+fn datastore_gql_conversion() {
+    let query = GqlQuery::new("SELECT * FROM users WHERE age >= @age AND country = @1")
+        .bind("age", 18)
+        .bind_cursor("start", vec![1, 2, 3])
+        .bind_positional("FR")
+        .allow_literals(true);
+    assert!(query.validate().is_ok());
+
+    let query = query.into_api("project");
+    assert!(query.allow_literals);
+    assert_eq!(query.named_bindings.len(), 2);
+    assert_eq!(query.positional_bindings.len(), 1);
+    assert!(matches!(
+        query.named_bindings["age"].parameter_type,
+        Some(ParameterType::Value(_)),
+    ));
+    assert_eq!(
+        query.named_bindings["start"].parameter_type,
+        Some(ParameterType::Cursor(vec![1, 2, 3])),
+    );
+}
+
+#[test]
+fn datastore_gql_validation() {
+    let invalid = vec![
+        GqlQuery::new(" "),
+        GqlQuery::new("SELECT * FROM users").bind("", 1),
+        GqlQuery::new("SELECT * FROM users").bind("1st", 1),
+        GqlQuery::new("SELECT * FROM users").bind("with space", 1),
+        GqlQuery::new("SELECT * FROM users").bind("__reserved__", 1),
+    ];
+    for query in invalid {
+        assert!(
+            matches!(query.validate(), Err(Error::InvalidArgument(_))),
+            "query should be invalid: {:?}",
+            query,
+        );
+    }
+    let valid = GqlQuery::new("SELECT * FROM users")
+        .bind("$name", 1)
+        .bind("_private", 2);
+    assert!(valid.validate().is_ok());
+}
@@ -0,0 +1,45 @@
+use crate::datastore::api::key::path_element::IdType;
+use crate::datastore::{allocate_ids_request, reserve_ids_request, Error, Key};
+
+fn ancestor() -> Key {
+    Key::new("parent").namespace("namespace").id("ancestor")
+}
+
+#[test]
+fn datastore_allocate_ids_request() {
+    let key = Key::new("kind").namespace("namespace").parent(ancestor());
+    let request = allocate_ids_request("project", std::slice::from_ref(&key)).unwrap();
+    assert_eq!(request.project_id, "project");
+    assert_eq!(request.keys.len(), 1);
+
+    let mut allocated = request.keys[0].clone();
+    let partition = allocated.partition_id.as_ref().unwrap();
+    assert_eq!(partition.project_id, "project");
+    assert_eq!(partition.namespace_id, "namespace");
+    let kinds: Vec<&str> = allocated.path.iter().map(|el| el.kind.as_str()).collect();
+    assert_eq!(kinds, vec!["parent", "kind"]);
+    assert_eq!(allocated.path[1].id_type, None);
+
+    // Completing the key, like the store does, preserves its namespace and ancestor.
+    allocated.path[1].id_type = Some(IdType::Id(42));
+    assert_eq!(Key::from(allocated), key.id(42));
+
+    let result = allocate_ids_request("project", &[Key::new("kind").id(10)]);
+    assert!(matches!(result, Err(Error::InvalidArgument(_))));
+}
+
+#[test]
+fn datastore_reserve_ids_request() {
+    let key = Key::new("kind")
+        .namespace("namespace")
+        .id(10)
+        .parent(ancestor());
+    let request = reserve_ids_request("project", std::slice::from_ref(&key)).unwrap();
+    assert_eq!(request.project_id, "project");
+    assert_eq!(request.database_id, "");
+    assert_eq!(request.keys.len(), 1);
+    assert_eq!(Key::from(request.keys[0].clone()), key);
+
+    let result = reserve_ids_request("project", &[Key::new("kind")]);
+    assert!(matches!(result, Err(Error::InvalidArgument(_))));
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::datastore::{
+    convert_key, decode_entities, lookup_results, Entity, Error, IntoValue, Key, LookupResults,
+    Value,
+};
+
+fn properties(name: &str) -> Value {
+    let mut properties = HashMap::new();
+    properties.insert(String::from("name"), name);
+    properties.into_value()
+}
+
+#[test]
+fn datastore_lookup_results_order() {
+    let keys: Vec<Key> = (1..=5).map(|id| Key::new("kind").id(id)).collect();
+
+    // The store returns entities and missing keys in any order.
+    let mut found = HashMap::new();
+    found.insert(keys[3].clone(), properties("four"));
+    found.insert(keys[0].clone(), properties("one"));
+    let mut missing = HashSet::new();
+    missing.insert(keys[2].clone());
+    missing.insert(keys[1].clone());
+    let deferred = vec![keys[4].clone()];
+
+    let results: LookupResults<HashMap<String, String>> =
+        lookup_results("project", &keys, found, missing, deferred).unwrap();
+    let found: Vec<(&Key, &str)> = results
+        .found
+        .iter()
+        .map(|(key, value)| (key, value["name"].as_str()))
+        .collect();
+    assert_eq!(found, vec![(&keys[0], "one"), (&keys[3], "four")]);
+    assert_eq!(results.missing, vec![keys[1].clone(), keys[2].clone()]);
+    assert_eq!(results.deferred, vec![keys[4].clone()]);
+
+    // Keys requested twice are reported twice.
+    let mut found = HashMap::new();
+    found.insert(keys[0].clone(), properties("one"));
+    let twice = [&keys[0], &keys[0]];
+    let results: LookupResults<Value> =
+        lookup_results("project", &twice, found, HashSet::new(), Vec::new()).unwrap();
+    assert_eq!(results.found.len(), 2);
+}
+
+#[test]
+fn datastore_lookup_results_ancestors() {
+    // The store returns keys with the namespace on every ancestor.
+    let requested = Key::new("child")
+        .id(1)
+        .namespace("dev")
+        .parent(Key::new("parent").id(1));
+    let absent = Key::new("child")
+        .id(2)
+        .namespace("dev")
+        .parent(Key::new("parent").id(1));
+    let returned = |key: &Key| Key::from(convert_key("project", key));
+    assert_ne!(returned(&requested), requested);
+
+    let mut found = HashMap::new();
+    found.insert(returned(&requested), properties("one"));
+    let mut missing = HashSet::new();
+    missing.insert(returned(&absent));
+
+    let keys = [&requested, &absent];
+    let results: LookupResults<HashMap<String, String>> =
+        lookup_results("project", &keys, found, missing, Vec::new()).unwrap();
+    assert_eq!(results.found.len(), 1);
+    assert_eq!(results.found[0].0, requested);
+    assert_eq!(results.found[0].1["name"], "one");
+    assert_eq!(results.missing, vec![absent]);
+}
+
+#[test]
+fn datastore_decode_entities() {
+    let entities = vec![
+        Entity::new(Key::new("kind").id(2), properties("two")).unwrap(),
+        Entity::new(Key::new("kind").id(1), properties("one")).unwrap(),
+    ];
+    let decoded: Vec<(Key, HashMap<String, String>)> = decode_entities(entities).unwrap();
+    let decoded: Vec<(&Key, &str)> = decoded
+        .iter()
+        .map(|(key, value)| (key, value["name"].as_str()))
+        .collect();
+    let (one, two) = (Key::new("kind").id(1), Key::new("kind").id(2));
+    assert_eq!(decoded, vec![(&two, "two"), (&one, "one")]);
+
+    let entities = vec![Entity::new(Key::new("kind").id(1), properties("one")).unwrap()];
+    let result = decode_entities::<HashMap<String, i64>>(entities);
+    assert!(matches!(result, Err(Error::Convert(_))));
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+
+use crate::datastore::api::mutation::{ConflictDetectionStrategy, Operation as ApiOperation};
+use crate::datastore::{Key, Mutation, Operation};
+
+#[test]
+fn datastore_mutation_conversion() {
+    let mut properties = HashMap::new();
+    properties.insert(String::from("name"), "value");
+
+    let insert = Mutation::put((Key::new("kind"), properties.clone())).unwrap();
+    assert!(matches!(insert.operation(), Operation::Insert(_)));
+    let upsert = Mutation::put((Key::new("kind").id(10), properties.clone())).unwrap();
+    assert!(matches!(upsert.operation(), Operation::Upsert(_)));
+
+    let update = Mutation::update((Key::new("kind").id("name"), properties))
+        .unwrap()
+        .base_version(42);
+    assert_eq!(update.key(), &Key::new("kind").id("name"));
+    let update = update.into_api("project");
+    assert!(matches!(update.operation, Some(ApiOperation::Update(_))));
+    assert_eq!(
+        update.conflict_detection_strategy,
+        Some(ConflictDetectionStrategy::BaseVersion(42)),
+    );
+
+    let delete = Mutation::delete(Key::new("kind").id(10)).into_api("project");
+    match delete.operation {
+        Some(ApiOperation::Delete(key)) => {
+            assert_eq!(key.partition_id.unwrap().project_id, "project");
+        }
+        _ => panic!("expected a delete mutation"),
+    }
+    assert_eq!(delete.conflict_detection_strategy, None);
+}
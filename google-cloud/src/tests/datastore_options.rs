@@ -0,0 +1,31 @@
+use chrono::NaiveDateTime;
+
+use crate::datastore::api::read_options::{ConsistencyType, ReadConsistency};
+use crate::datastore::ReadOptions;
+
+#[test]
+fn datastore_read_options_conversion() {
+    let consistency = |options: ReadOptions| options.into_api().consistency_type.unwrap();
+
+    assert_eq!(
+        consistency(ReadOptions::Strong),
+        ConsistencyType::ReadConsistency(ReadConsistency::Strong as i32),
+    );
+    assert_eq!(
+        consistency(ReadOptions::Eventual),
+        ConsistencyType::ReadConsistency(ReadConsistency::Eventual as i32),
+    );
+    assert_eq!(
+        consistency(ReadOptions::Transaction(vec![1, 2, 3])),
+        ConsistencyType::Transaction(vec![1, 2, 3]),
+    );
+
+    let time = "2021-03-24T12:30:00.250".parse::<NaiveDateTime>().unwrap();
+    match consistency(ReadOptions::ReadTime(time)) {
+        ConsistencyType::ReadTime(timestamp) => {
+            assert_eq!(timestamp.seconds, 1616589000);
+            assert_eq!(timestamp.nanos, 250_000_000);
+        }
+        other => panic!("expected a read time, got {:?}", other),
+    }
+}
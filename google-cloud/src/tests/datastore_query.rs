@@ -0,0 +1,89 @@
+use crate::datastore::api;
+use crate::datastore::api::query_result_batch::MoreResultsType;
+use crate::datastore::{Error, Filter, Key, MoreResults, Order, Query};
+
+#[test]
+fn datastore_filter_builder() {
+    let filter = Filter::prop("age")
+        .gt(10)
+        .or(Filter::prop("firstname").is_in(vec!["john", "jane"]))
+        .or(Filter::prop("lastname").ne("doe"));
+    match filter {
+        Filter::Or(filters) => assert_eq!(filters.len(), 3),
+        _ => panic!("expected an `Or` filter"),
+    }
+}
+
+#[test]
+fn datastore_query_validation() {
+    let valid = Query::new("users")
+        .filter(Filter::prop("age").gt(10).and(Filter::prop("age").lte(20)))
+        .filter(Filter::prop("firstname").is_in(vec!["john", "jane"]))
+        .filter(Filter::HasAncestor(Key::new("org").id(1)))
+        .order(Order::Asc("age".into()));
+    assert!(valid.validate().is_ok());
+
+    let invalid = vec![
+        Query::new("users")
+            .filter(Filter::prop("age").gt(10))
+            .filter(Filter::prop("height").lt(180)),
+        Query::new("users").filter(
+            Filter::prop("age")
+                .gt(10)
+                .or(Filter::prop("name").ne("john")),
+        ),
+        Query::new("users")
+            .filter(Filter::prop("age").ne(10))
+            .order(Order::Desc("name".into())),
+        Query::new("users")
+            .filter(Filter::prop("age").is_in(vec![1, 2]))
+            .filter(Filter::prop("name").not_in(vec!["john"])),
+        Query::new("users").filter(Filter::prop("age").is_in(Vec::<i64>::new())),
+        Query::new("users").filter(Filter::prop("age").is_in(0..=Filter::MAX_VALUES as i64)),
+        Query::new("users").filter(Filter::Or(Vec::new())),
+        Query::new("users").filter(Filter::HasAncestor(Key::new("org"))),
+        Query::new("users").ancestor(Key::new("org")),
+    ];
+    for query in invalid {
+        assert!(
+            matches!(query.validate(), Err(Error::InvalidArgument(_))),
+            "query should be invalid: {:?}",
+            query,
+        );
+    }
+}
+
+#[test]
+fn datastore_more_results_conversion() {
+    let cases = [
+        (MoreResultsType::NotFinished, MoreResults::NotFinished),
+        (
+            MoreResultsType::MoreResultsAfterLimit,
+            MoreResults::MoreResultsAfterLimit,
+        ),
+        (
+            MoreResultsType::MoreResultsAfterCursor,
+            MoreResults::MoreResultsAfterCursor,
+        ),
+        (MoreResultsType::NoMoreResults, MoreResults::NoMoreResults),
+    ];
+    for (value, expected) in cases.iter() {
+        assert_eq!(MoreResults::from(*value as i32), *expected);
+    }
+}
+
+#[test]
+fn datastore_query_advance() {
+    let mut query = api::Query {
+        offset: 10,
+        limit: Some(25),
+        ..Default::default()
+    };
+    query.advance(4, 0, vec![1]);
+    assert_eq!((query.offset, query.limit), (6, Some(25)));
+    query.advance(6, 10, vec![2]);
+    assert_eq!((query.offset, query.limit), (0, Some(15)));
+    query.advance(0, 15, vec![3]);
+    assert_eq!((query.offset, query.limit), (0, Some(0)));
+    assert_eq!(query.start_cursor, vec![3]);
+}
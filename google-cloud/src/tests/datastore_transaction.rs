@@ -0,0 +1,223 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use tonic::transport::Channel;
+
+use crate::authorize::TokenManager;
+use crate::datastore::api::datastore_client::DatastoreClient;
+use crate::datastore::{
+    is_retryable, retry_backoff, run_transaction, Client, Error, Key, Operation, Transaction,
+    TransactionConfig, TransactionRunner,
+};
+
+const SERVICE_ACCOUNT: &str = include_str!("../../samples/test-service-account.json");
+
+/// Creates a transaction whose client never connects (nothing is sent until commit).
+fn offline_transaction(read_only: bool) -> Transaction {
+    let creds = json::from_str(SERVICE_ACCOUNT).expect("invalid service account");
+    let channel = Channel::from_static("http://localhost").connect_lazy();
+    let client = Client {
+        project_name: String::from("test-project"),
+        service: DatastoreClient::new(channel.expect("could not create channel")),
+        token_manager: Arc::new(tokio::sync::Mutex::new(TokenManager::new(
+            creds,
+            Client::SCOPES.as_ref(),
+        ))),
+    };
+
+    Transaction {
+        client,
+        id: b"transaction".to_vec(),
+        read_only,
+        mutations: Arc::new(Mutex::new(Vec::new())),
+    }
+}
+
+#[test]
+fn datastore_transaction_config() {
+    let config = TransactionConfig::default();
+    assert_eq!(config.max_attempts, 5);
+    assert!(!config.read_only);
+    assert_eq!(config.read_time, None);
+
+    let config = TransactionConfig::default().max_attempts(0);
+    assert_eq!(config.max_attempts, 1);
+
+    let time: NaiveDateTime = "2021-03-24T12:00:00".parse().unwrap();
+    let config = TransactionConfig::default().read_time(time);
+    assert!(config.read_only);
+    assert_eq!(config.read_time, Some(time));
+}
+
+#[test]
+fn datastore_transaction_retries() {
+    let aborted = Error::from(tonic::Status::aborted("too much contention"));
+    assert!(is_retryable(&aborted));
+    let not_found = Error::from(tonic::Status::not_found("no such entity"));
+    assert!(!is_retryable(&not_found));
+    let invalid = Error::InvalidArgument(String::from("invalid"));
+    assert!(!is_retryable(&invalid));
+
+    assert_eq!(retry_backoff(1), Duration::from_millis(200));
+    assert_eq!(retry_backoff(2), Duration::from_millis(400));
+    assert_eq!(retry_backoff(6), Duration::from_millis(6400));
+    assert_eq!(retry_backoff(20), Duration::from_millis(6400));
+}
+
+#[tokio::test]
+async fn datastore_transaction_mutations() {
+    let transaction = offline_transaction(false);
+    assert!(!transaction.is_read_only());
+    assert_eq!(transaction.id(), b"transaction");
+
+    // Clones share the same buffer, like the ones given to `Client::transaction` closures.
+    let clone = transaction.clone();
+    let mut properties = HashMap::new();
+    properties.insert(String::from("name"), "value");
+    transaction
+        .upsert((Key::new("kind").id(1), properties))
+        .unwrap();
+    clone.delete(Key::new("kind").id(2)).unwrap();
+
+    let mutations = transaction.mutations.lock().unwrap();
+    assert_eq!(mutations.len(), 2);
+    assert!(matches!(mutations[0].operation(), Operation::Upsert(_)));
+    assert_eq!(mutations[1].key(), &Key::new("kind").id(2));
+    assert!(matches!(mutations[1].operation(), Operation::Delete(_)));
+}
+
+#[tokio::test]
+async fn datastore_transaction_read_only_mutations() {
+    let transaction = offline_transaction(true);
+    assert!(transaction.is_read_only());
+
+    let result = transaction.delete(Key::new("kind").id(1));
+    assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    assert!(transaction.mutations.lock().unwrap().is_empty());
+}
+
+/// Runs transactions without a store, committing them with queued results.
+#[derive(Default)]
+struct StubRunner {
+    /// The previous transaction given to each `begin`.
+    previous: Vec<Option<Vec<u8>>>,
+    /// The results of the next commits (success once empty).
+    commits: VecDeque<Result<(), Error>>,
+    committed: usize,
+    rolled_back: usize,
+}
+
+impl TransactionRunner for StubRunner {
+    async fn begin(
+        &mut self,
+        config: &TransactionConfig,
+        previous: Option<Vec<u8>>,
+    ) -> Result<Transaction, Error> {
+        self.previous.push(previous);
+        let mut transaction = offline_transaction(config.read_only);
+        transaction.id = format!("transaction-{}", self.previous.len()).into_bytes();
+        Ok(transaction)
+    }
+
+    async fn commit(&mut self, _: Transaction) -> Result<(), Error> {
+        self.committed += 1;
+        self.commits.pop_front().unwrap_or(Ok(()))
+    }
+
+    async fn rollback(&mut self, _: Transaction) -> Result<(), Error> {
+        self.rolled_back += 1;
+        Ok(())
+    }
+}
+
+fn aborted() -> Error {
+    Error::from(tonic::Status::aborted("too much contention"))
+}
+
+#[tokio::test]
+async fn datastore_transaction_retries_aborted_commits() {
+    let mut runner = StubRunner::default();
+    runner.commits.push_back(Err(aborted()));
+    runner.commits.push_back(Err(aborted()));
+
+    let result = run_transaction(&mut runner, TransactionConfig::default(), |_| async {
+        Ok::<_, Error>(42)
+    })
+    .await;
+    assert_eq!(result.unwrap(), 42);
+    assert_eq!(runner.committed, 3);
+    assert_eq!(runner.rolled_back, 0);
+
+    // Each retry names the transaction it replaces.
+    let previous = vec![
+        None,
+        Some(b"transaction-1".to_vec()),
+        Some(b"transaction-2".to_vec()),
+    ];
+    assert_eq!(runner.previous, previous);
+}
+
+#[tokio::test]
+async fn datastore_transaction_retries_aborted_closures() {
+    let mut runner = StubRunner::default();
+    let mut calls = 0;
+    let result = run_transaction(&mut runner, TransactionConfig::default(), |_| {
+        calls += 1;
+        let result = if calls == 1 {
+            Err(aborted())
+        } else {
+            Ok(calls)
+        };
+        async move { result }
+    })
+    .await;
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(runner.rolled_back, 1);
+    assert_eq!(runner.committed, 1);
+    assert_eq!(runner.previous[1], Some(b"transaction-1".to_vec()));
+}
+
+#[tokio::test]
+async fn datastore_transaction_rolls_back_on_errors() {
+    let mut runner = StubRunner::default();
+    let result: Result<(), Error> =
+        run_transaction(&mut runner, TransactionConfig::default(), |_| async {
+            Err(Error::InvalidArgument(String::from("invalid")))
+        })
+        .await;
+    assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    assert_eq!(runner.previous.len(), 1);
+    assert_eq!(runner.rolled_back, 1);
+    assert_eq!(runner.committed, 0);
+
+    // Commits failing for other reasons than contention are not retried either.
+    let mut runner = StubRunner::default();
+    let not_found = Error::from(tonic::Status::not_found("no such entity"));
+    runner.commits.push_back(Err(not_found));
+    let result = run_transaction(&mut runner, TransactionConfig::default(), |_| async {
+        Ok::<_, Error>(())
+    })
+    .await;
+    assert!(
+        matches!(result, Err(Error::Status(ref status)) if status.code() == tonic::Code::NotFound)
+    );
+    assert_eq!(runner.previous.len(), 1);
+    assert_eq!(runner.committed, 1);
+}
+
+#[tokio::test]
+async fn datastore_transaction_max_attempts() {
+    let mut runner = StubRunner::default();
+    for _ in 0..5 {
+        runner.commits.push_back(Err(aborted()));
+    }
+
+    let config = TransactionConfig::default().max_attempts(2);
+    let result = run_transaction(&mut runner, config, |_| async { Ok::<_, Error>(()) }).await;
+    assert!(matches!(result, Err(ref err) if is_retryable(err)));
+    assert_eq!(runner.previous.len(), 2);
+    assert_eq!(runner.committed, 2);
+    assert_eq!(runner.commits.len(), 3);
+}
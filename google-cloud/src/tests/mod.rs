@@ -1,14 +1,52 @@
+#[cfg(feature = "datastore")]
+mod datastore_aggregation;
+#[cfg(feature = "datastore")]
+mod datastore_gql;
+#[cfg(feature = "datastore")]
+mod datastore_ids;
+#[cfg(feature = "datastore")]
+mod datastore_lookup;
+#[cfg(feature = "datastore")]
+mod datastore_mutation;
+#[cfg(feature = "datastore")]
+mod datastore_options;
+#[cfg(feature = "datastore")]
+mod datastore_query;
+#[cfg(feature = "datastore")]
+mod datastore_transaction;
 #[cfg(feature = "pubsub")]
 mod pubsub;
 #[cfg(feature = "storage")]
 mod storage;
+#[cfg(feature = "storage")]
+mod storage_acl;
+#[cfg(feature = "storage")]
+mod storage_batch;
+#[cfg(feature = "storage")]
+mod storage_checksum;
+#[cfg(feature = "storage")]
+mod storage_config;
+#[cfg(feature = "testing")]
+mod storage_emulator;
+#[cfg(feature = "storage")]
+mod storage_encryption;
+#[cfg(feature = "storage")]
+mod storage_hmac;
+#[cfg(feature = "storage")]
+mod storage_notification;
+#[cfg(feature = "storage")]
+mod storage_signing;
+#[cfg(feature = "storage")]
+mod storage_sync;
 #[cfg(feature = "vision")]
 mod vision;
 
-use crate::authorize::ApplicationCredentials;
-
-fn load_creds() -> ApplicationCredentials {
+#[cfg(any(
+    feature = "pubsub",
+    feature = "vision",
+    all(feature = "storage", not(feature = "testing")),
+))]
+fn load_creds() -> crate::authorize::ApplicationCredentials {
     let creds = std::env::var("GCP_TEST_CREDENTIALS").expect("env GCP_TEST_CREDENTIALS not set");
-    json::from_str::<ApplicationCredentials>(&creds)
-        .expect("incorrect application credentials format")
+    json::from_str(&creds).expect("incorrect application credentials format")
 }
@@ -115,7 +115,7 @@ async fn pubsub_sends_and_receives_message_successfully() {
             max_messages: 1,
         })
         .await;
-    assert_eq!(received.is_none(), true);
+    assert!(received.is_none());
     println!("OK !");
 
     //? Delete the subscription.
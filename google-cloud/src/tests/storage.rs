@@ -11,15 +11,49 @@ macro_rules! assert_ok {
     };
 }
 
-async fn setup_client() -> Result<storage::Client, storage::Error> {
+/// Get a test setting, which falls back to a default when running against the fake server.
+#[cfg(not(feature = "testing"))]
+macro_rules! test_var {
+    ($name:literal, $default:literal) => {
+        env!($name)
+    };
+}
+
+#[cfg(feature = "testing")]
+macro_rules! test_var {
+    ($name:literal, $default:literal) => {
+        option_env!($name).unwrap_or($default)
+    };
+}
+
+/// Keeps the server the client is connected to running, for as long as it is held.
+#[cfg(not(feature = "testing"))]
+type TestServer = ();
+
+#[cfg(feature = "testing")]
+type TestServer = storage::testing::FakeServer;
+
+#[cfg(not(feature = "testing"))]
+async fn setup_client() -> Result<(storage::Client, TestServer), storage::Error> {
     let creds = super::load_creds();
-    storage::Client::from_credentials(env!("GCP_TEST_PROJECT"), creds).await
+    let project_name = test_var!("GCP_TEST_PROJECT", "test-project");
+    let client = storage::Client::from_credentials(project_name, creds).await?;
+    Ok((client, ()))
+}
+
+#[cfg(feature = "testing")]
+async fn setup_client() -> Result<(storage::Client, TestServer), storage::Error> {
+    let server = storage::testing::FakeServer::start().await?;
+    let client = server
+        .client(test_var!("GCP_TEST_PROJECT", "test-project"))
+        .await?;
+    Ok((client, server))
 }
 
 #[tokio::test]
 async fn storage_lists_buckets() {
     //? Setup test client.
-    let mut client = assert_ok!(setup_client().await);
+    let (mut client, _server) = assert_ok!(setup_client().await);
 
     //? List all buckets of the project.
     let buckets = assert_ok!(client.buckets().await);
@@ -30,13 +64,24 @@ async fn storage_lists_buckets() {
     }
 }
 
+#[tokio::test]
+async fn storage_service_account_email() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Get the project's service agent email.
+    let email = assert_ok!(client.service_account_email().await);
+    assert!(email.contains('@'));
+    assert!(email.ends_with(".gserviceaccount.com"));
+}
+
 #[tokio::test]
 async fn storage_create_and_delete_bucket() {
     //? Setup test client.
-    let mut client = assert_ok!(setup_client().await);
+    let (mut client, _server) = assert_ok!(setup_client().await);
 
     //? Access existing bucket or create it, if non-existant.
-    let bucket_name = env!("GCP_TEST_BUCKET").to_lowercase();
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
     let bucket = match client.bucket(bucket_name.as_str()).await {
         Ok(bucket) => Ok(bucket),
         Err(_) => client.create_bucket(bucket_name.as_str()).await,
@@ -46,11 +91,18 @@ async fn storage_create_and_delete_bucket() {
 
     //? Access existing object in that bucket or create it, if non-existant.
     let object_data = r#"{"type":"sample","from":"google-cloud-rs"}"#;
-    let object = match bucket.object(env!("GCP_TEST_OBJECT")).await {
+    let object = match bucket
+        .object(test_var!("GCP_TEST_OBJECT", "test-object"))
+        .await
+    {
         Ok(object) => Ok(object),
         Err(_) => {
             bucket
-                .create_object(env!("GCP_TEST_OBJECT"), object_data, "application/json")
+                .create_object(
+                    test_var!("GCP_TEST_OBJECT", "test-object"),
+                    object_data,
+                    "application/json",
+                )
                 .await
         }
     };
@@ -70,3 +122,261 @@ async fn storage_create_and_delete_bucket() {
     //? Delete the bucket.
     assert_ok!(bucket.delete().await);
 }
+
+#[tokio::test]
+async fn storage_object_preconditions() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Create an object, only if it does not exist yet.
+    let object_name = format!(
+        "{}-preconditions",
+        test_var!("GCP_TEST_OBJECT", "test-object")
+    );
+    let conditions = storage::Conditions::default().if_generation_match(0);
+    let object = bucket
+        .create_object_with_conditions(&object_name, "first", "text/plain", conditions.clone())
+        .await;
+    let object = assert_ok!(object);
+
+    //? Creating it a second time must fail the precondition.
+    let result = bucket
+        .create_object_with_conditions(&object_name, "second", "text/plain", conditions)
+        .await;
+    assert!(matches!(result, Err(storage::Error::PreconditionFailed)));
+
+    //? Delete that object.
+    assert_ok!(object.delete().await);
+}
+
+#[tokio::test]
+async fn storage_object_holds() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Create an object and place a temporary hold on it.
+    let object_name = format!("{}-holds", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let mut object = assert_ok!(
+        bucket
+            .create_object(&object_name, "held", "text/plain")
+            .await
+    );
+    let conditions =
+        storage::Conditions::default().if_metageneration_match(object.metageneration());
+    assert_ok!(
+        object
+            .set_temporary_hold_with_conditions(true, conditions)
+            .await
+    );
+    assert!(object.temporary_hold());
+
+    //? A stale metageneration must fail the precondition.
+    let conditions = storage::Conditions::default().if_metageneration_match(1);
+    let result = object
+        .set_event_based_hold_with_conditions(true, conditions)
+        .await;
+    assert!(matches!(result, Err(storage::Error::PreconditionFailed)));
+
+    //? Held objects cannot be deleted.
+    assert!(object.clone().delete().await.is_err());
+
+    //? Release the hold and delete that object.
+    assert_ok!(object.set_temporary_hold(false).await);
+    assert!(!object.temporary_hold());
+    assert_ok!(object.delete().await);
+}
+
+#[tokio::test]
+async fn storage_object_copy_and_compose() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Create a source object.
+    let source_name = format!("{}-source", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let source = bucket
+        .create_object(&source_name, "hello ", "text/plain")
+        .await;
+    let mut source = assert_ok!(source);
+
+    //? Copy it within the same bucket.
+    let copy_name = format!("{}-copy", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let copy = assert_ok!(source.copy_to(&bucket_name, &copy_name).await);
+    assert_eq!(copy.size(), source.size());
+
+    //? Compose both objects into a new one.
+    let composed_name = format!("{}-composed", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let sources = vec![
+        storage::ComposeSource::from(&source),
+        storage::ComposeSource::from(&copy),
+    ];
+    let config = storage::ObjectConfig::default().content_type("text/plain");
+    let composed = bucket.compose(&composed_name, sources, config).await;
+    let mut composed = assert_ok!(composed);
+    assert_eq!(assert_ok!(composed.get().await), b"hello hello ");
+
+    //? Delete all three objects.
+    assert_ok!(composed.delete().await);
+    assert_ok!(copy.delete().await);
+    assert_ok!(source.delete().await);
+}
+
+#[tokio::test]
+async fn storage_object_streams() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Write an object as a stream of chunks.
+    let object_name = format!("{}-stream", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let config = storage::UploadConfig::default().md5(true);
+    let mut writer = assert_ok!(bucket.writer(&object_name, config).await);
+    for _ in 0..3 {
+        assert_ok!(writer.write(b"hello ").await);
+    }
+    assert_eq!(writer.bytes_written(), 18);
+    let mut object = assert_ok!(writer.finish().await);
+    assert_eq!(object.size(), 18);
+
+    //? Read it back as a stream.
+    let mut reader = assert_ok!(object.reader().await);
+    let data = assert_ok!(reader.read_to_end().await);
+    assert_eq!(data, b"hello hello hello ");
+
+    //? Delete that object.
+    assert_ok!(object.delete().await);
+}
+
+#[tokio::test]
+async fn storage_parallel_transfers() {
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Write a local file, spanning multiple parts.
+    let data: Vec<u8> = (0..5000u32).map(|idx| (idx % 251) as u8).collect();
+    let dir = std::env::temp_dir();
+    let source_path = dir.join("google-cloud-rs-transfer-source");
+    let target_path = dir.join("google-cloud-rs-transfer-target");
+    assert_ok!(std::fs::write(&source_path, &data));
+
+    //? Upload it as a parallel composite upload.
+    let object_name = format!("{}-transfer", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let transfer = storage::TransferConfig::default()
+        .concurrency(2)
+        .part_size(1024);
+    let config = storage::UploadConfig::default();
+    let object = bucket
+        .upload_file(&source_path, &object_name, config, transfer.clone())
+        .await;
+    let mut object = assert_ok!(object);
+    assert_eq!(object.size(), data.len() as u64);
+
+    //? Download it back as a sliced download.
+    assert_ok!(object.download_to_file(&target_path, transfer).await);
+    let downloaded = assert_ok!(std::fs::read(&target_path));
+    assert_eq!(downloaded, data);
+
+    //? Delete that object and the local files.
+    assert_ok!(object.delete().await);
+    assert_ok!(std::fs::remove_file(&source_path));
+    assert_ok!(std::fs::remove_file(&target_path));
+}
+
+#[tokio::test]
+async fn storage_directory_sync() {
+    use storage::sync::{self, SyncAction, SyncConfig};
+
+    //? Setup test client.
+    let (mut client, _server) = assert_ok!(setup_client().await);
+
+    //? Access existing bucket or create it, if non-existant.
+    let bucket_name = test_var!("GCP_TEST_BUCKET", "test-bucket").to_lowercase();
+    let bucket = match client.bucket(bucket_name.as_str()).await {
+        Ok(bucket) => Ok(bucket),
+        Err(_) => client.create_bucket(bucket_name.as_str()).await,
+    };
+    let mut bucket = assert_ok!(bucket);
+
+    //? Write a local directory tree.
+    let dir = std::env::temp_dir().join("google-cloud-rs-sync");
+    let source = dir.join("source");
+    let target = dir.join("target");
+    assert_ok!(std::fs::create_dir_all(source.join("nested")));
+    assert_ok!(std::fs::write(source.join("a.txt"), "first"));
+    assert_ok!(std::fs::write(
+        source.join("nested").join("b.txt"),
+        "second"
+    ));
+    assert_ok!(std::fs::write(source.join("skipped.log"), "skipped"));
+
+    //? Plan the upload with a dry run.
+    let prefix = format!("{}-sync", test_var!("GCP_TEST_OBJECT", "test-object"));
+    let config = assert_ok!(SyncConfig::default().exclude("*.log"));
+    let report =
+        sync::upload_dir(&source, &mut bucket, &prefix, config.clone().dry_run(true)).await;
+    let report = assert_ok!(report);
+    assert!(report.is_dry_run());
+    assert_eq!(
+        report.actions(),
+        &[
+            SyncAction::Upload(String::from("a.txt")),
+            SyncAction::Upload(String::from("nested/b.txt")),
+        ]
+    );
+
+    //? Upload, then check that a second run has nothing left to do.
+    assert_ok!(sync::upload_dir(&source, &mut bucket, &prefix, config.clone()).await);
+    let report = assert_ok!(sync::upload_dir(&source, &mut bucket, &prefix, config.clone()).await);
+    assert!(report.actions().is_empty());
+    assert_eq!(report.unchanged(), 2);
+
+    //? Download the prefix into another directory.
+    assert_ok!(sync::download_dir(&mut bucket, &prefix, &target, config).await);
+    let data = assert_ok!(std::fs::read_to_string(target.join("nested").join("b.txt")));
+    assert_eq!(data, "second");
+
+    //? Delete the synchronized objects and the local directories.
+    let objects = assert_ok!(bucket.objects_with_prefix(&prefix).await);
+    for object in objects {
+        assert_ok!(object.delete().await);
+    }
+    assert_ok!(std::fs::remove_dir_all(&dir));
+}
@@ -0,0 +1,86 @@
+use std::convert::TryFrom;
+
+use crate::storage::{AclEntity, AclRole, IamBinding, IamCondition, IamPolicy, ProjectTeam};
+
+#[test]
+fn storage_acl_entity_roundtrip() {
+    let entities = vec![
+        (
+            AclEntity::User(String::from("jane@example.com")),
+            "user-jane@example.com",
+        ),
+        (
+            AclEntity::Group(String::from("team@example.com")),
+            "group-team@example.com",
+        ),
+        (
+            AclEntity::Domain(String::from("example.com")),
+            "domain-example.com",
+        ),
+        (
+            AclEntity::Project(ProjectTeam::Viewers, String::from("123456")),
+            "project-viewers-123456",
+        ),
+        (AclEntity::AllUsers, "allUsers"),
+        (AclEntity::AllAuthenticatedUsers, "allAuthenticatedUsers"),
+    ];
+
+    for (entity, expected) in entities {
+        assert_eq!(entity.to_string(), expected);
+        let parsed = AclEntity::try_from(String::from(expected));
+        assert_eq!(parsed.expect("could not parse entity"), entity);
+    }
+
+    assert!(AclEntity::try_from(String::from("project-nobody-1")).is_err());
+    assert!(AclEntity::try_from(String::from("someone")).is_err());
+}
+
+#[test]
+fn storage_acl_role_serialization() {
+    let role = json::to_value(AclRole::Owner).expect("could not serialize role");
+    assert_eq!(role, json::json!("OWNER"));
+    let role: AclRole = json::from_str(r#""READER""#).expect("could not deserialize role");
+    assert_eq!(role, AclRole::Reader);
+}
+
+#[test]
+fn storage_iam_policy_conditional_binding() {
+    let mut policy = IamPolicy::default();
+    policy.add_binding(IamBinding::new("roles/storage.objectViewer").member("allUsers"));
+    assert_eq!(policy.version(), 0);
+
+    let condition = IamCondition::new(
+        "expires",
+        r#"request.time < timestamp("2030-01-01T00:00:00Z")"#,
+    );
+    let binding = IamBinding::new("roles/storage.objectAdmin")
+        .member("user:jane@example.com")
+        .condition(condition);
+    policy.add_binding(binding);
+    assert_eq!(policy.version(), 3);
+
+    let value = json::to_value(&policy).expect("could not serialize policy");
+    assert_eq!(
+        value,
+        json::json!({
+            "version": 3,
+            "bindings": [
+                {
+                    "role": "roles/storage.objectViewer",
+                    "members": ["allUsers"],
+                },
+                {
+                    "role": "roles/storage.objectAdmin",
+                    "members": ["user:jane@example.com"],
+                    "condition": {
+                        "title": "expires",
+                        "expression": "request.time < timestamp(\"2030-01-01T00:00:00Z\")",
+                    },
+                },
+            ],
+        }),
+    );
+
+    policy.remove_role("roles/storage.objectViewer");
+    assert_eq!(policy.bindings().len(), 1);
+}
@@ -0,0 +1,114 @@
+use crate::authorize::ApplicationCredentials;
+use crate::storage::batch::{encode, parse, BatchPart};
+use crate::storage::{AclEntity, AclRole, Client, Conditions, ObjectConfig};
+
+async fn offline_client() -> Client {
+    let creds: ApplicationCredentials = json::from_value(json::json!({
+        "type": "service_account",
+        "project_id": "test-project",
+        "private_key_id": "",
+        "private_key": "",
+        "client_email": "test@test-project.iam.gserviceaccount.com",
+        "client_id": "",
+        "auth_uri": "",
+        "token_uri": "",
+        "auth_provider_x509_cert_url": "",
+        "client_x509_cert_url": "",
+    }))
+    .expect("could not deserialize credentials");
+    Client::from_credentials("test-project", creds)
+        .await
+        .expect("could not create client")
+}
+
+#[tokio::test]
+async fn storage_batch_encoding() {
+    let client = offline_client().await;
+    let mut batch = client.batch();
+    batch
+        .delete_object_with_conditions(
+            "bucket",
+            "dir/a.txt",
+            Conditions::default().if_generation_match(42),
+        )
+        .patch_object(
+            "bucket",
+            "b.txt",
+            ObjectConfig::default().content_type("text/plain"),
+        )
+        .insert_object_acl("bucket", "b.txt", &AclEntity::AllUsers, AclRole::Reader);
+    assert_eq!(batch.len(), 3);
+
+    let body = encode(&batch.requests, "BOUNDARY");
+    let expected = concat!(
+        "--BOUNDARY\r\n",
+        "content-type: application/http\r\n",
+        "content-id: <1>\r\n",
+        "\r\n",
+        "DELETE /storage/v1/b/bucket/o/dir%2Fa%2Etxt?ifGenerationMatch=42 HTTP/1.1\r\n",
+        "\r\n",
+        "--BOUNDARY\r\n",
+        "content-type: application/http\r\n",
+        "content-id: <2>\r\n",
+        "\r\n",
+        "PATCH /storage/v1/b/bucket/o/b%2Etxt HTTP/1.1\r\n",
+        "content-type: application/json; charset=UTF-8\r\n",
+        "content-length: 28\r\n",
+        "\r\n",
+        "{\"contentType\":\"text/plain\"}\r\n",
+        "--BOUNDARY\r\n",
+        "content-type: application/http\r\n",
+        "content-id: <3>\r\n",
+        "\r\n",
+        "POST /storage/v1/b/bucket/o/b%2Etxt/acl HTTP/1.1\r\n",
+        "content-type: application/json; charset=UTF-8\r\n",
+        "content-length: 37\r\n",
+        "\r\n",
+        "{\"entity\":\"allUsers\",\"role\":\"READER\"}\r\n",
+        "--BOUNDARY--\r\n",
+    );
+    assert_eq!(String::from_utf8(body).unwrap(), expected);
+}
+
+#[test]
+fn storage_batch_response_parsing() {
+    let response = concat!(
+        "--batch_abc\r\n",
+        "Content-Type: application/http\r\n",
+        "Content-ID: <response-2>\r\n",
+        "\r\n",
+        "HTTP/1.1 412 Precondition Failed\r\n",
+        "Content-Type: application/json; charset=UTF-8\r\n",
+        "\r\n",
+        "{\"error\":{\"code\":412,\"message\":\"Precondition Failed\"}}\r\n",
+        "\r\n",
+        "--batch_abc\r\n",
+        "Content-Type: application/http\r\n",
+        "Content-ID: <response-1>\r\n",
+        "\r\n",
+        "HTTP/1.1 204 No Content\r\n",
+        "Content-Length: 0\r\n",
+        "\r\n",
+        "\r\n",
+        "--batch_abc--\r\n",
+    );
+
+    let parts = parse("batch_abc", response);
+    assert_eq!(
+        parts,
+        vec![
+            BatchPart {
+                index: Some(1),
+                status: 412,
+                body: String::from(
+                    "{\"error\":{\"code\":412,\"message\":\"Precondition Failed\"}}"
+                ),
+            },
+            BatchPart {
+                index: Some(0),
+                status: 204,
+                body: String::new(),
+            },
+        ],
+    );
+}
@@ -0,0 +1,70 @@
+use reqwest::header::{HeaderMap, HeaderValue};
+
+use crate::storage::checksum::{is_transcoded, Checksums, Hasher};
+
+const DATA: &[u8] = b"123456789";
+const CRC32C: &str = "4waSgw==";
+const MD5: &str = "JfnnlDI7RTiF9RgfG2JNCw==";
+
+#[test]
+fn storage_checksum_compute() {
+    let checksums = Checksums::compute(DATA, true, true);
+    assert_eq!(checksums.crc32c.as_deref(), Some(CRC32C));
+    assert_eq!(checksums.md5.as_deref(), Some(MD5));
+
+    let mut hasher = Hasher::new(true, false);
+    for chunk in DATA.chunks(2) {
+        hasher.update(chunk);
+    }
+    let checksums = hasher.finish();
+    assert_eq!(checksums.crc32c.as_deref(), Some(CRC32C));
+    assert_eq!(checksums.md5, None);
+}
+
+#[test]
+fn storage_checksum_verify_headers() {
+    let mut headers = HeaderMap::new();
+    let value = format!("crc32c={}", CRC32C);
+    headers.append("x-goog-hash", HeaderValue::from_str(&value).unwrap());
+    let value = format!("md5={}", MD5);
+    headers.append("x-goog-hash", HeaderValue::from_str(&value).unwrap());
+
+    let checksums = Checksums::from_headers(&headers);
+    assert_eq!(checksums, Checksums::compute(DATA, true, true));
+    assert!(checksums.verify(DATA).is_ok());
+
+    let result = checksums.verify(b"12345678");
+    match result {
+        Err(crate::storage::Error::ChecksumMismatch {
+            algorithm,
+            expected,
+            ..
+        }) => {
+            assert_eq!(algorithm, "crc32c");
+            assert_eq!(expected, CRC32C);
+        }
+        _ => panic!("expected a checksum mismatch"),
+    }
+
+    let mut headers = HeaderMap::new();
+    let value = format!("crc32c=AAAAAA==, md5={}", MD5);
+    headers.insert("x-goog-hash", HeaderValue::from_str(&value).unwrap());
+    let checksums = Checksums::from_headers(&headers);
+    assert_eq!(checksums.crc32c.as_deref(), Some("AAAAAA=="));
+    assert_eq!(checksums.md5.as_deref(), Some(MD5));
+}
+
+#[test]
+fn storage_checksum_transcoding() {
+    let mut headers = HeaderMap::new();
+    assert!(!is_transcoded(&headers));
+
+    headers.insert(
+        "x-goog-stored-content-encoding",
+        HeaderValue::from_static("gzip"),
+    );
+    assert!(is_transcoded(&headers));
+
+    headers.insert("content-encoding", HeaderValue::from_static("gzip"));
+    assert!(!is_transcoded(&headers));
+}
@@ -0,0 +1,160 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::storage::api::bucket::{BucketPatchResource, BucketResource};
+use crate::storage::{BucketConfig, CorsRule, LifecycleRule, PublicAccessPrevention};
+
+#[test]
+fn storage_bucket_config_serialization() {
+    let config = BucketConfig::default()
+        .location("EU")
+        .storage_class("STANDARD")
+        .versioning(true)
+        .lifecycle_rule(LifecycleRule::delete().age(30).is_live(false))
+        .lifecycle_rule(
+            LifecycleRule::set_storage_class("COLDLINE")
+                .created_before("2020-01-01".parse::<NaiveDate>().unwrap())
+                .matches_storage_class("STANDARD"),
+        )
+        .retention_period(Duration::days(1))
+        .cors_rule(
+            CorsRule::default()
+                .origin("*")
+                .method("GET")
+                .max_age_seconds(3600),
+        )
+        .label("env", "test")
+        .requester_pays(false)
+        .uniform_bucket_level_access(true)
+        .public_access_prevention(PublicAccessPrevention::Enforced);
+
+    let value = json::to_value(BucketPatchResource::from(config)).expect("could not serialize");
+    assert_eq!(
+        value,
+        json::json!({
+            "location": "EU",
+            "storageClass": "STANDARD",
+            "versioning": { "enabled": true },
+            "lifecycle": {
+                "rule": [
+                    {
+                        "action": { "type": "Delete" },
+                        "condition": { "age": 30, "isLive": false },
+                    },
+                    {
+                        "action": { "type": "SetStorageClass", "storageClass": "COLDLINE" },
+                        "condition": {
+                            "createdBefore": "2020-01-01",
+                            "matchesStorageClass": ["STANDARD"],
+                        },
+                    },
+                ],
+            },
+            "retentionPolicy": { "retentionPeriod": "86400" },
+            "cors": [
+                {
+                    "origin": ["*"],
+                    "method": ["GET"],
+                    "responseHeader": [],
+                    "maxAgeSeconds": 3600,
+                },
+            ],
+            "labels": { "env": "test" },
+            "billing": { "requesterPays": false },
+            "iamConfiguration": {
+                "uniformBucketLevelAccess": { "enabled": true },
+                "publicAccessPrevention": "enforced",
+            },
+        }),
+    );
+
+    let value = json::to_value(BucketPatchResource::from(BucketConfig::default()));
+    assert_eq!(value.expect("could not serialize"), json::json!({}));
+}
+
+#[test]
+fn storage_bucket_resource_deserialization() {
+    let resource = json::json!({
+        "kind": "storage#bucket",
+        "id": "test-bucket",
+        "selfLink": "https://www.googleapis.com/storage/v1/b/test-bucket",
+        "projectNumber": "123456",
+        "name": "test-bucket",
+        "timeCreated": "2020-01-01T00:00:00.000Z",
+        "updated": "2020-01-01T00:00:00.000Z",
+        "metageneration": "2",
+        "location": "EU",
+        "locationType": "multi-region",
+        "storageClass": "STANDARD",
+        "etag": "CAI=",
+        "retentionPolicy": {
+            "retentionPeriod": "86400",
+            "effectiveTime": "2020-01-01T00:00:00.000Z",
+        },
+        "iamConfiguration": {
+            "uniformBucketLevelAccess": { "enabled": false },
+            "publicAccessPrevention": "inherited",
+        },
+    });
+
+    let resource: BucketResource = json::from_value(resource).expect("could not deserialize");
+    let retention = resource.retention_policy.expect("missing retention policy");
+    assert_eq!(retention.retention_period, "86400");
+    assert_eq!(retention.is_locked, None);
+}
+
+#[test]
+fn storage_lifecycle_rule_conditions() {
+    let date = "2021-06-30".parse::<NaiveDate>().unwrap();
+    let config = BucketConfig::default()
+        .lifecycle_rule(
+            LifecycleRule::abort_incomplete_multipart_upload()
+                .age(7)
+                .matches_prefix("uploads/")
+                .matches_suffix(".tmp"),
+        )
+        .lifecycle_rule(
+            LifecycleRule::delete()
+                .num_newer_versions(3)
+                .days_since_noncurrent_time(10)
+                .noncurrent_time_before(date),
+        )
+        .lifecycle_rule(
+            LifecycleRule::set_storage_class("ARCHIVE")
+                .custom_time_before(date)
+                .days_since_custom_time(90),
+        );
+
+    let value = json::to_value(BucketPatchResource::from(config)).expect("could not serialize");
+    assert_eq!(
+        value,
+        json::json!({
+            "lifecycle": {
+                "rule": [
+                    {
+                        "action": { "type": "AbortIncompleteMultipartUpload" },
+                        "condition": {
+                            "age": 7,
+                            "matchesPrefix": ["uploads/"],
+                            "matchesSuffix": [".tmp"],
+                        },
+                    },
+                    {
+                        "action": { "type": "Delete" },
+                        "condition": {
+                            "numNewerVersions": 3,
+                            "daysSinceNoncurrentTime": 10,
+                            "noncurrentTimeBefore": "2021-06-30",
+                        },
+                    },
+                    {
+                        "action": { "type": "SetStorageClass", "storageClass": "ARCHIVE" },
+                        "condition": {
+                            "customTimeBefore": "2021-06-30",
+                            "daysSinceCustomTime": 90,
+                        },
+                    },
+                ],
+            },
+        }),
+    );
+}
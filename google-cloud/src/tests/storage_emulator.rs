@@ -0,0 +1,179 @@
+use chrono::{Duration, SubsecRound, Utc};
+
+use crate::storage::testing::FakeServer;
+use crate::storage::{
+    Client, Conditions, Error, ObjectRetention, RetentionMode, RewriteConfig, UploadConfig,
+};
+
+#[tokio::test]
+async fn storage_emulator_host() {
+    let server = FakeServer::start().await.expect("could not start server");
+    let host = server.addr().to_string();
+    let client = Client::from_emulator_host("test-project", host).await;
+    let mut client = client.expect("could not create client");
+
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+    for name in ["a/1", "a/2", "b"].iter() {
+        let data = "0123456789";
+        bucket
+            .create_object(name, data, "text/plain")
+            .await
+            .unwrap();
+    }
+
+    let objects = bucket.objects_with_prefix("a/").await.unwrap();
+    let names: Vec<&str> = objects.iter().map(|object| object.name()).collect();
+    assert_eq!(names, vec!["a/1", "a/2"]);
+
+    let mut object = bucket.object("b").await.unwrap();
+    assert_eq!(object.size(), 10);
+    assert_eq!(object.content_type(), "text/plain");
+    assert_eq!(object.get().await.unwrap(), b"0123456789");
+    assert_eq!(object.get_range(2..5).await.unwrap(), b"234");
+
+    let conditions = Conditions::default().if_generation_match(object.generation() + 1);
+    let result = object.get_with_conditions(conditions).await;
+    assert!(matches!(result, Err(Error::PreconditionFailed)));
+
+    let buckets = client.buckets().await.unwrap();
+    assert_eq!(buckets.len(), 1);
+    let bucket = buckets.into_iter().next().unwrap();
+    assert!(bucket.delete().await.is_err());
+}
+
+#[tokio::test]
+async fn storage_emulator_grpc_streams() {
+    let server = FakeServer::start().await.expect("could not start server");
+    let mut client = server.grpc_client("test-project").await.unwrap();
+    assert!(client.uses_grpc());
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+
+    // Spans multiple messages, both ways.
+    let data: Vec<u8> = (0..5_000_000u32).map(|idx| (idx % 251) as u8).collect();
+    let config = UploadConfig::default().md5(true);
+    let mut writer = bucket.writer("large", config).await.unwrap();
+    for chunk in data.chunks(1_000_000) {
+        writer.write(chunk).await.unwrap();
+    }
+    assert_eq!(writer.bytes_written(), data.len() as u64);
+    assert!(writer.committed_size().await.unwrap() <= data.len() as u64);
+    let mut object = writer.finish().await.unwrap();
+    assert_eq!(object.size(), data.len() as u64);
+
+    let mut reader = object.reader().await.unwrap();
+    let mut chunks = 0;
+    let mut read = Vec::new();
+    while let Some(chunk) = reader.chunk().await.unwrap() {
+        read.extend_from_slice(&chunk);
+        chunks += 1;
+    }
+    assert!(chunks > 1);
+    assert_eq!(read, data);
+
+    // The JSON API sees the same data.
+    assert_eq!(object.get().await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn storage_emulator_resumable_writer() {
+    let server = FakeServer::start().await.expect("could not start server");
+    let mut client = server.client("test-project").await.unwrap();
+    assert!(!client.uses_grpc());
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+
+    // Spans multiple chunks of the upload session.
+    let data: Vec<u8> = (0..9_000_000u32).map(|idx| (idx % 251) as u8).collect();
+    let mut writer = bucket
+        .writer("large", UploadConfig::default())
+        .await
+        .unwrap();
+    writer.write(&data).await.unwrap();
+    assert_eq!(writer.committed_size().await.unwrap(), 8 * 1024 * 1024);
+    let mut object = writer.finish().await.unwrap();
+    assert_eq!(object.size(), data.len() as u64);
+
+    let mut reader = object.reader().await.unwrap();
+    assert_eq!(reader.read_to_end().await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn storage_emulator_rewrite_in_multiple_calls() {
+    let server = FakeServer::start().await.expect("could not start server");
+    let mut client = server.client("test-project").await.unwrap();
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+
+    let data: Vec<u8> = (0..3_500_000u32).map(|idx| (idx % 251) as u8).collect();
+    let mut object = bucket
+        .create_object("source", data.clone(), "application/octet-stream")
+        .await
+        .unwrap();
+
+    // The fake server only writes the object once the rewrite token covers all of its data.
+    let config = RewriteConfig::default().max_bytes_per_call(1024 * 1024);
+    let mut rewritten = object
+        .rewrite_to("test-bucket", "destination", config)
+        .await
+        .unwrap();
+    assert_eq!(rewritten.name(), "destination");
+    assert_eq!(rewritten.size(), data.len() as u64);
+    assert_eq!(rewritten.get().await.unwrap(), data);
+}
+
+#[tokio::test]
+async fn storage_emulator_object_retention() {
+    let server = FakeServer::start().await.expect("could not start server");
+    let mut client = server.client("test-project").await.unwrap();
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+    let mut object = bucket
+        .create_object("retained", "data", "text/plain")
+        .await
+        .unwrap();
+    assert_eq!(object.retention(), None);
+
+    let until = Utc::now().trunc_subsecs(0) + Duration::hours(1);
+    let retention = ObjectRetention::new(RetentionMode::Unlocked, until);
+    object
+        .set_retention(Some(retention.clone()), false)
+        .await
+        .unwrap();
+    assert_eq!(object.retention(), Some(retention));
+    assert!(object.clone().delete().await.is_err());
+
+    // Removing an unlocked retention must be explicitly overridden.
+    assert!(object.set_retention(None, false).await.is_err());
+    object.set_retention(None, true).await.unwrap();
+    assert_eq!(object.retention(), None);
+
+    let retention = ObjectRetention::new(RetentionMode::Locked, until);
+    object.set_retention(Some(retention), false).await.unwrap();
+    assert_eq!(object.retention().unwrap().mode(), RetentionMode::Locked);
+    assert!(object.set_retention(None, true).await.is_err());
+}
+
+#[tokio::test]
+async fn storage_emulator_sync_skips_unsafe_names() {
+    use crate::storage::sync::{self, SyncAction, SyncConfig};
+
+    let server = FakeServer::start().await.expect("could not start server");
+    let mut client = server.client("test-project").await.unwrap();
+    let mut bucket = client.create_bucket("test-bucket").await.unwrap();
+    for name in ["sync/safe.txt", "sync/../../escaped.txt", "sync/a//b.txt"].iter() {
+        bucket
+            .create_object(name, "data", "text/plain")
+            .await
+            .unwrap();
+    }
+
+    let dir = std::env::temp_dir().join("google-cloud-rs-sync-unsafe");
+    let target = dir.join("nested").join("target");
+    let config = SyncConfig::default();
+    let report = sync::download_dir(&mut bucket, "sync", &target, config).await;
+    let report = report.unwrap();
+    assert_eq!(
+        report.actions(),
+        &[SyncAction::Download(String::from("safe.txt"))]
+    );
+    assert!(target.join("safe.txt").is_file());
+    assert!(!dir.join("escaped.txt").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
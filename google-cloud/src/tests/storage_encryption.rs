@@ -0,0 +1,40 @@
+use crate::storage::EncryptionKey;
+
+const KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+const KEY_SHA256: &str = "Yw3NKWbEM2aRElRIu7JbT/QSpJxzLbLIq8G4WBvXEN0=";
+
+#[test]
+fn storage_encryption_key_encoding() {
+    let mut bytes = [0; 32];
+    for (idx, byte) in bytes.iter_mut().enumerate() {
+        *byte = idx as u8;
+    }
+    let key = EncryptionKey::new(bytes);
+    assert_eq!(key.to_base64(), KEY);
+    assert_eq!(key.sha256(), KEY_SHA256);
+
+    let parsed = EncryptionKey::from_base64(KEY).expect("could not parse key");
+    assert_eq!(parsed, key);
+
+    let debug = format!("{:?}", key);
+    assert!(debug.contains(KEY_SHA256));
+    assert!(!debug.contains(KEY));
+}
+
+#[test]
+fn storage_encryption_key_validation() {
+    let short = EncryptionKey::from_base64("AAECAwQFBgcICQoLDA0ODw==");
+    assert!(matches!(
+        short,
+        Err(crate::storage::Error::InvalidArgument(_))
+    ));
+    let invalid = EncryptionKey::from_base64("not base64!");
+    assert!(matches!(
+        invalid,
+        Err(crate::storage::Error::InvalidArgument(_))
+    ));
+
+    let first = EncryptionKey::generate().expect("could not generate key");
+    let second = EncryptionKey::generate().expect("could not generate key");
+    assert_ne!(first, second);
+}
@@ -0,0 +1,50 @@
+use crate::storage::api::hmac_key::{HmacKeyResource, HmacKeyUpdateResource};
+use crate::storage::{HmacKey, HmacKeyMetadata, HmacKeyState};
+
+#[test]
+fn storage_hmac_key_deserialization() {
+    let resource = json::json!({
+        "kind": "storage#hmacKey",
+        "metadata": {
+            "kind": "storage#hmacKeyMetadata",
+            "id": "test-project/GOOG1EXAMPLE",
+            "selfLink": "https://www.googleapis.com/storage/v1/projects/test-project/hmacKeys/GOOG1EXAMPLE",
+            "accessId": "GOOG1EXAMPLE",
+            "projectId": "test-project",
+            "serviceAccountEmail": "test@test-project.iam.gserviceaccount.com",
+            "state": "ACTIVE",
+            "timeCreated": "2020-01-01T00:00:00.000Z",
+            "updated": "2020-01-01T00:00:00.000Z",
+            "etag": "MQ==",
+        },
+        "secret": "bGoa+V7g/yqDXvKRqq+JTFn4uQZbPiQJo4pf9RzJ",
+    });
+
+    let resource: HmacKeyResource = json::from_value(resource).expect("could not deserialize");
+    let key = HmacKey {
+        metadata: HmacKeyMetadata {
+            resource: resource.metadata,
+        },
+        secret: resource.secret,
+    };
+    assert_eq!(key.metadata().access_id(), "GOOG1EXAMPLE");
+    assert_eq!(key.metadata().state(), HmacKeyState::Active);
+    assert_eq!(
+        key.metadata().time_created().map(|time| time.timestamp()),
+        Some(1_577_836_800)
+    );
+
+    let debug = format!("{:?}", key);
+    assert!(debug.contains("GOOG1EXAMPLE"));
+    assert!(!debug.contains(key.secret()));
+}
+
+#[test]
+fn storage_hmac_key_update_serialization() {
+    let body = HmacKeyUpdateResource {
+        state: HmacKeyState::Inactive,
+        etag: None,
+    };
+    let value = json::to_value(&body).expect("could not serialize");
+    assert_eq!(value, json::json!({ "state": "INACTIVE" }));
+}
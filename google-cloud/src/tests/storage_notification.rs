@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::storage::{EventType, NotificationConfig, ObjectChangeEvent, PayloadFormat};
+
+fn attributes() -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let entries = [
+        (
+            "notificationConfig",
+            "projects/_/buckets/my-bucket/notificationConfigs/1",
+        ),
+        ("eventType", "OBJECT_FINALIZE"),
+        ("payloadFormat", "JSON_API_V1"),
+        ("bucketId", "my-bucket"),
+        ("objectId", "path/to/file.txt"),
+        ("objectGeneration", "1600000000000000"),
+        ("eventTime", "2020-09-13T12:26:40.000000Z"),
+        ("overwroteGeneration", "1500000000000000"),
+    ];
+    for (key, value) in entries.iter() {
+        attributes.insert(String::from(*key), String::from(*value));
+    }
+    attributes
+}
+
+#[test]
+fn storage_notification_event_parsing() {
+    let payload = json::json!({
+        "kind": "storage#object",
+        "id": "my-bucket/path/to/file.txt/1600000000000000",
+        "selfLink": "https://www.googleapis.com/storage/v1/b/my-bucket/o/path%2Fto%2Ffile.txt",
+        "name": "path/to/file.txt",
+        "bucket": "my-bucket",
+        "generation": "1600000000000000",
+        "metageneration": "1",
+        "contentType": "text/plain",
+        "timeCreated": "2020-09-13T12:26:40.000Z",
+        "updated": "2020-09-13T12:26:40.000Z",
+        "storageClass": "STANDARD",
+        "timeStorageClassUpdated": "2020-09-13T12:26:40.000Z",
+        "size": "11",
+        "md5Hash": "XrY7u+Ae7tCTyyK7j1rNww==",
+        "mediaLink": "https://www.googleapis.com/download/storage/v1/b/my-bucket/o/path%2Fto%2Ffile.txt?generation=1600000000000000&alt=media",
+        "metadata": { "origin": "test" },
+        "crc32c": "yZRlqg==",
+        "etag": "CICAgICAgICAAQ=="
+    });
+    let data = json::to_vec(&payload).unwrap();
+
+    let event = ObjectChangeEvent::parse(&attributes(), &data).expect("could not parse event");
+    assert_eq!(event.event_type(), EventType::ObjectFinalize);
+    assert_eq!(event.bucket(), "my-bucket");
+    assert_eq!(event.object(), "path/to/file.txt");
+    assert_eq!(event.generation(), 1_600_000_000_000_000);
+    assert_eq!(event.overwrote_generation(), Some(1_500_000_000_000_000));
+    assert_eq!(event.overwritten_by_generation(), None);
+    assert_eq!(event.event_time().timestamp(), 1_600_000_000);
+    assert_eq!(event.size(), Some(11));
+    assert_eq!(event.content_type(), Some("text/plain"));
+    assert_eq!(event.crc32c(), Some("yZRlqg=="));
+    let metadata = event.metadata().expect("missing metadata");
+    assert_eq!(metadata.get("origin").map(String::as_str), Some("test"));
+}
+
+#[test]
+fn storage_notification_event_validation() {
+    let mut attributes = attributes();
+    attributes.insert(String::from("payloadFormat"), String::from("NONE"));
+    let event = ObjectChangeEvent::parse(&attributes, &[]).expect("could not parse event");
+    assert_eq!(event.size(), None);
+
+    attributes.insert(String::from("eventType"), String::from("OBJECT_EXPLODE"));
+    let invalid = ObjectChangeEvent::parse(&attributes, &[]);
+    assert!(matches!(
+        invalid,
+        Err(crate::storage::Error::InvalidArgument(_))
+    ));
+
+    attributes.remove("bucketId");
+    attributes.insert(String::from("eventType"), String::from("OBJECT_DELETE"));
+    let missing = ObjectChangeEvent::parse(&attributes, &[]);
+    assert!(matches!(missing, Err(crate::storage::Error::Convert(_))));
+}
+
+#[test]
+fn storage_notification_config_serialization() {
+    let body = NotificationConfig::new("my-topic")
+        .event_type(EventType::ObjectFinalize)
+        .event_type(EventType::ObjectMetadataUpdate)
+        .object_name_prefix("logs/")
+        .payload_format(PayloadFormat::None)
+        .into_resource("my-project");
+    let expected = json::json!({
+        "topic": "//pubsub.googleapis.com/projects/my-project/topics/my-topic",
+        "eventTypes": ["OBJECT_FINALIZE", "OBJECT_METADATA_UPDATE"],
+        "payloadFormat": "NONE",
+        "objectNamePrefix": "logs/"
+    });
+    assert_eq!(json::to_value(&body).unwrap(), expected);
+}
+
+#[test]
+fn storage_notification_topic_names() {
+    let topics = [
+        "my-topic",
+        "projects/my-project/topics/my-topic",
+        "//pubsub.googleapis.com/projects/my-project/topics/my-topic",
+    ];
+    for topic in topics.iter() {
+        let body = NotificationConfig::new(*topic)
+            .custom_attribute("origin", "test")
+            .into_resource("my-project");
+        let expected = json::json!({
+            "topic": "//pubsub.googleapis.com/projects/my-project/topics/my-topic",
+            "customAttributes": { "origin": "test" },
+            "payloadFormat": "JSON_API_V1"
+        });
+        assert_eq!(json::to_value(&body).unwrap(), expected, "{}", topic);
+    }
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+
+use crate::storage::sync::local_path;
+use crate::storage::Error;
+
+#[test]
+fn storage_sync_local_paths() {
+    let dir = Path::new("target");
+    let path = local_path(dir, "nested/file.txt").unwrap();
+    assert_eq!(path, dir.join("nested").join("file.txt"));
+    let path = local_path(dir, "..file.txt").unwrap();
+    assert_eq!(path, dir.join("..file.txt"));
+
+    let unsafe_paths = [
+        "",
+        "/etc/passwd",
+        "../file.txt",
+        "nested/../../file.txt",
+        "nested/./file.txt",
+        "nested//file.txt",
+        "nested/",
+    ];
+    for relative in unsafe_paths.iter() {
+        let result = local_path(dir, relative);
+        assert!(
+            matches!(result, Err(Error::InvalidArgument(_))),
+            "{}",
+            relative
+        );
+    }
+}
@@ -10,7 +10,6 @@ use tonic::{IntoRequest, Request};
 use crate::authorize::{ApplicationCredentials, TokenManager, TLS_CERTS};
 use crate::vision::api;
 use crate::vision::api::image_annotator_client::ImageAnnotatorClient;
-use crate::vision::api::product_search_client::ProductSearchClient;
 use crate::vision::{
     Error, FaceAnnotation, FaceDetectionConfig, Image, TextAnnotation, TextDetectionConfig,
 };
@@ -18,9 +17,10 @@ use crate::vision::{
 /// The Cloud Vision client, tied to a specific project.
 #[derive(Clone)]
 pub struct Client {
+    // Image annotation requests are not scoped to a project.
+    #[allow(dead_code)]
     pub(crate) project_name: String,
     pub(crate) img_annotator: ImageAnnotatorClient<Channel>,
-    pub(crate) product_search: ProductSearchClient<Channel>,
     pub(crate) token_manager: Arc<Mutex<TokenManager>>,
 }
 
@@ -70,8 +70,7 @@ impl Client {
 
         Ok(Client {
             project_name: project_name.into(),
-            img_annotator: ImageAnnotatorClient::new(channel.clone()),
-            product_search: ProductSearchClient::new(channel),
+            img_annotator: ImageAnnotatorClient::new(channel),
             token_manager: Arc::new(Mutex::new(TokenManager::new(
                 creds,
                 Client::SCOPES.as_ref(),
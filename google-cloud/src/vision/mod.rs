@@ -4,6 +4,7 @@ mod face;
 mod image;
 mod likelihood;
 mod text;
+#[allow(dead_code, clippy::all)]
 mod api {
     pub mod rpc {
         include!("api/google.rpc.rs");
@@ -29,7 +30,6 @@ mod api {
         }
     }
     pub use self::cloud::vision::v1::*;
-    pub use self::r#type::*;
 }
 
 pub use self::bounding_box::*;
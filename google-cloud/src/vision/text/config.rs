@@ -1,6 +1,7 @@
 use crate::vision::api;
 
 /// Represents the text detection's configuration.
+#[derive(Default)]
 pub struct TextDetectionConfig {
     pub(crate) language_hints: Vec<String>,
 }
@@ -14,14 +15,6 @@ impl TextDetectionConfig {
     }
 }
 
-impl Default for TextDetectionConfig {
-    fn default() -> TextDetectionConfig {
-        TextDetectionConfig {
-            language_hints: Vec::new(),
-        }
-    }
-}
-
 impl From<TextDetectionConfig> for api::ImageContext {
     fn from(config: TextDetectionConfig) -> api::ImageContext {
         api::ImageContext {